@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+// trigram profiles of the languages written in alphabets shared by several languages
+const PROFILES: &str = include_str!("language_profiles.txt");
+
+// languages recognised by their script alone: (ISO 639-3 code, stop word list code, name, script)
+const SCRIPT_LANGUAGES: &[(&str, &str, &str, Script)] = &[
+    ("zho", "-", "Chinese", Script::Han),
+    ("jpn", "-", "Japanese", Script::Kana),
+    ("kor", "-", "Korean", Script::Hangul),
+    ("tha", "-", "Thai", Script::Thai),
+    ("ell", "el", "Greek", Script::Greek),
+];
+
+// the number of trigrams in a profile, and the distance charged for a trigram that is not in it
+const PROFILE_SIZE: usize = 300;
+
+// documents with fewer distinct trigrams than this are too short to tell
+const MIN_TRIGRAMS: usize = 20;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Script {
+    Latin,
+    Cyrillic,
+    Arabic,
+    Devanagari,
+    Hebrew,
+    Han,
+    Kana,
+    Hangul,
+    Thai,
+    Greek,
+}
+
+impl Script {
+    fn of(c: char) -> Option<Script> {
+        match c {
+            'a'..='z' | 'A'..='Z' | '\u{c0}'..='\u{24f}' | '\u{1e00}'..='\u{1eff}' => Some(Script::Latin),
+            '\u{370}'..='\u{3ff}' | '\u{1f00}'..='\u{1fff}' => Some(Script::Greek),
+            '\u{400}'..='\u{52f}' => Some(Script::Cyrillic),
+            '\u{5d0}'..='\u{5ff}' => Some(Script::Hebrew),
+            '\u{600}'..='\u{6ff}' | '\u{750}'..='\u{77f}' | '\u{fb50}'..='\u{fdff}' | '\u{fe70}'..='\u{feff}' => Some(Script::Arabic),
+            '\u{900}'..='\u{97f}' => Some(Script::Devanagari),
+            '\u{e00}'..='\u{e7f}' => Some(Script::Thai),
+            '\u{3040}'..='\u{30ff}' | '\u{31f0}'..='\u{31ff}' => Some(Script::Kana),
+            '\u{1100}'..='\u{11ff}' | '\u{ac00}'..='\u{d7af}' => Some(Script::Hangul),
+            c if crate::segmenter::is_ideograph(c) => Some(Script::Han),
+            _ => None,
+        }
+    }
+
+    fn parse(name: &str) -> Option<Script> {
+        match name {
+            "Latin" => Some(Script::Latin),
+            "Cyrillic" => Some(Script::Cyrillic),
+            "Arabic" => Some(Script::Arabic),
+            "Devanagari" => Some(Script::Devanagari),
+            "Hebrew" => Some(Script::Hebrew),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * A language that can be detected.
+ */
+#[derive(Clone, Copy)]
+pub struct Language {
+    // ISO 639-3 code, such as "eng"
+    pub code: &'static str,
+    pub name: &'static str,
+    // the code of its stop word list in the stop-words crate (the NLTK lists), if it has one
+    stop_words: Option<&'static str>,
+    script: Script,
+}
+
+impl Language {
+    /**
+     * Get the stop words of this language, or None if there is no list for it.
+     */
+    pub fn stop_words(&self) -> Option<&'static [&'static str]> {
+        self.stop_words.map(stop_words::get)
+    }
+}
+
+/**
+ * Identifies the language of a document by comparing the ranking of its character trigrams with the profile
+ * of each language written in the same script (the "out-of-place" measure of Cavnar and Trenkle).
+ * Chinese, Japanese, Korean, Thai and Greek are identified by their script alone.
+ */
+pub struct LanguageDetector {
+    // each language, with the rank of each trigram of its profile, most frequent first (none for languages known by their script)
+    languages: Vec<(Language, HashMap<String, usize>)>,
+}
+
+impl LanguageDetector {
+    pub fn new() -> LanguageDetector {
+        let mut languages: Vec<(Language, HashMap<String, usize>)> = SCRIPT_LANGUAGES.iter()
+            .map(|&(code, stop_words, name, script)| {
+                (Language { code, name, stop_words: Some(stop_words).filter(|code| *code != "-"), script }, HashMap::new())
+            })
+            .collect();
+
+        for line in PROFILES.lines().filter(|line| !line.starts_with('#') && !line.is_empty()) {
+            let mut fields = line.split('\t');
+            let (code, stop_words, name, script) = match (fields.next(), fields.next(), fields.next(), fields.next().and_then(Script::parse)) {
+                (Some(code), Some(stop_words), Some(name), Some(script)) => (code, stop_words, name, script),
+                _ => panic!("malformed language profile \"{}\"", line),
+            };
+            languages.push((
+                Language { code, name, stop_words: Some(stop_words).filter(|code| *code != "-"), script },
+                fields.enumerate().map(|(rank, trigram)| (trigram.to_string(), rank)).collect(),
+            ));
+        }
+        LanguageDetector { languages }
+    }
+
+    /**
+     * Get the language of a document from its raw text, or None if there is too little text to tell.
+     * The text is read before it is split into words, so that no tokenizer setting (such as dropping letters
+     * outside ASCII) takes away the letters the languages are told apart by.
+     */
+    pub fn detect<'a>(&self, lines: impl Iterator<Item = &'a String>) -> Option<Language> {
+        let words: Vec<String> = lines
+            .flat_map(|line| line.split(|c: char| !c.is_alphabetic() && c != '\''))
+            .map(|word| word.trim_matches('\''))
+            .filter(|word| !word.is_empty())
+            .map(String::from)
+            .collect();
+
+        let script = dominant_script(&words)?;
+        let mut candidates = self.languages.iter().filter(|(language, _)| language.script == script);
+        if matches!(script, Script::Han | Script::Kana | Script::Hangul | Script::Thai | Script::Greek) {
+            return candidates.next().map(|(language, _)| *language);
+        }
+
+        let ranks = rank_trigrams(&words);
+        if ranks.len() < MIN_TRIGRAMS {
+            return None;
+        }
+
+        candidates
+            .min_by_key(|(_, profile)| {
+                ranks.iter()
+                    .map(|(trigram, rank)| profile.get(trigram).map_or(PROFILE_SIZE, |profile_rank| profile_rank.abs_diff(*rank)))
+                    .sum::<usize>()
+            })
+            .map(|(language, _)| *language)
+    }
+}
+
+/**
+ * Get the script most of the letters of the words are written in. Japanese text counts as kana whenever it has any,
+ * since it mixes kana with Han characters.
+ */
+fn dominant_script(words: &[String]) -> Option<Script> {
+    let mut counts: HashMap<Script, usize> = HashMap::new();
+    for script in words.iter().flat_map(|word| word.chars()).filter_map(Script::of) {
+        *counts.entry(script).or_insert(0) += 1;
+    }
+
+    let han = counts.get(&Script::Han).copied().unwrap_or(0);
+    if let Some(kana) = counts.get_mut(&Script::Kana) {
+        *kana += han;
+    }
+    counts.into_iter().max_by_key(|(_, count)| *count).map(|(script, _)| script)
+}
+
+/**
+ * Rank the character trigrams of the words, most frequent first, keeping as many as a profile holds.
+ * Words are joined by '_', which also marks the start and end of the text, as in the profiles.
+ */
+fn rank_trigrams(words: &[String]) -> HashMap<String, usize> {
+    let text: Vec<char> = format!("_{}_", words.join("_")).to_lowercase().chars().collect();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for trigram in text.windows(3) {
+        *counts.entry(trigram.iter().collect()).or_insert(0) += 1;
+    }
+
+    let mut sorted: Vec<(String, usize)> = counts.into_iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sorted.into_iter().take(PROFILE_SIZE).enumerate().map(|(rank, (trigram, _))| (trigram, rank)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_languages_from_raw_text() {
+        let detector = LanguageDetector::new();
+        let russian = ["Все счастливые семьи похожи друг на друга, каждая несчастливая семья несчастлива по-своему.".to_string(),
+            "Все смешалось в доме Облонских. Жена узнала, что муж был в связи с француженкою-гувернанткой.".to_string()];
+        assert_eq!(detector.detect(russian.iter()).map(|language| language.code), Some("rus"));
+        let chinese = ["我们今天去北京大学学习中文。".to_string()];
+        assert_eq!(detector.detect(chinese.iter()).map(|language| language.code), Some("zho"));
+        // too short to tell
+        let short = ["Hello there.".to_string()];
+        assert!(detector.detect(short.iter()).is_none());
+    }
+}
@@ -0,0 +1,572 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+
+pub const SENTENCE_START: &str = "<s>";
+pub const SENTENCE_END: &str = "</s>";
+pub const UNKNOWN: &str = "<unk>";
+
+// the ids of the markers, which are the first words of every vocabulary
+const START_ID: u32 = 0;
+const UNKNOWN_ID: u32 = 2;
+
+// discounts for counts of 1, 2 and 3 or more when there are too few n-grams to estimate them
+const FALLBACK_DISCOUNTS: [f64; 3] = [0.5, 1.0, 1.5];
+
+// log10 probability ARPA files give words that are never predicted, such as <s>
+const NEVER: f64 = -99.0;
+
+// generated sentences are cut off at this many words, in case the chain keeps going round a loop
+const MAX_SENTENCE_LENGTH: usize = 200;
+
+/**
+ * Counts the n-grams of every order up to the model's order in sentences padded with <s> and </s>,
+ * to train a language model from.
+ */
+pub struct NgramCounter {
+    order: usize,
+    words: Vec<String>,
+    ids: HashMap<String, u32>,
+    // counts[n - 1]: the count of each n-gram, as word ids
+    counts: Vec<HashMap<Vec<u32>, u32>>,
+}
+
+impl NgramCounter {
+    pub fn new(order: usize) -> NgramCounter {
+        let mut counter = NgramCounter { order, words: Vec::new(), ids: HashMap::new(), counts: vec![HashMap::new(); order] };
+        for marker in [SENTENCE_START, SENTENCE_END, UNKNOWN] {
+            counter.id(marker);
+        }
+        counter
+    }
+
+    fn id(&mut self, word: &str) -> u32 {
+        if let Some(id) = self.ids.get(word) {
+            return *id;
+        }
+        let id = self.words.len() as u32;
+        self.words.push(word.to_string());
+        self.ids.insert(word.to_string(), id);
+        id
+    }
+
+    pub fn add_sentence(&mut self, sentence: &[String]) {
+        let mut padded = vec![START_ID];
+        for word in sentence {
+            let id = self.id(word);
+            padded.push(id);
+        }
+        padded.push(self.id(SENTENCE_END));
+
+        *self.counts[0].entry(vec![START_ID]).or_insert(0) += 1;
+        for end in 1..padded.len() {
+            for n in 1..=self.order.min(end + 1) {
+                *self.counts[n - 1].entry(padded[end + 1 - n..=end].to_vec()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /**
+     * Estimate an interpolated modified Kneser-Ney model (Chen and Goodman) from the counts, stored as a backoff model:
+     * each n-gram keeps its interpolated probability and each context the weight of its lower-order distribution,
+     * which is exactly the interpolated estimate for the words never seen after it.
+     * Every order but the highest is estimated from continuation counts (how many different words precede an n-gram)
+     * rather than raw counts, except n-grams starting with <s>, which nothing can precede.
+     * Unigrams are interpolated with the uniform distribution over the vocabulary, <unk> included.
+     */
+    pub fn train(self) -> LanguageModel {
+        let order = self.order;
+        let mut adjusted: Vec<HashMap<Vec<u32>, u32>> = vec![HashMap::new(); order];
+        adjusted[order - 1] = self.counts[order - 1].clone();
+        for n in 1..order {
+            for ngram in self.counts[n].keys().filter(|ngram| ngram[1] != START_ID) {
+                *adjusted[n - 1].entry(ngram[1..].to_vec()).or_insert(0) += 1;
+            }
+            for (ngram, count) in self.counts[n - 1].iter().filter(|(ngram, _)| ngram[0] == START_ID) {
+                adjusted[n - 1].insert(ngram.clone(), *count);
+            }
+        }
+        // <s> is never predicted, so it takes no part in the unigram distribution
+        adjusted[0].remove(&vec![START_ID]);
+
+        let mut ngrams: Vec<HashMap<Vec<u32>, Entry>> = vec![HashMap::new(); order];
+        for n in 1..=order {
+            let discounts = discounts(&adjusted[n - 1]);
+            let discount = |count: u32| discounts[count.min(3) as usize - 1];
+
+            // for each context: the total of its adjusted counts and the weight left for the lower order
+            let mut contexts: HashMap<&[u32], (f64, f64)> = HashMap::new();
+            for (ngram, &count) in &adjusted[n - 1] {
+                let (total, left) = contexts.entry(&ngram[..n - 1]).or_insert((0.0, 0.0));
+                *total += count as f64;
+                *left += discount(count);
+            }
+
+            let vocabulary_size = (adjusted[0].len() + usize::from(!adjusted[0].contains_key(&vec![UNKNOWN_ID]))) as f64;
+            let mut probabilities: Vec<(Vec<u32>, f64)> = Vec::new();
+            for (ngram, &count) in &adjusted[n - 1] {
+                let (total, left) = contexts[&ngram[..n - 1]];
+                let lower = match n {
+                    1 => 1.0 / vocabulary_size,
+                    _ => 10f64.powf(ngrams[n - 2][&ngram[1..]].probability),
+                };
+                probabilities.push((ngram.clone(), (count as f64 - discount(count)) / total + left / total * lower));
+            }
+            if n == 1 {
+                let (total, left) = contexts.get(&[][..]).copied().unwrap_or((1.0, 1.0));
+                let unknown = ngrams[0].entry(vec![UNKNOWN_ID]).or_insert(Entry { probability: 0.0, backoff: None });
+                unknown.probability = (left / total / vocabulary_size).log10();
+                ngrams[0].insert(vec![START_ID], Entry { probability: NEVER, backoff: None });
+            }
+            for (ngram, probability) in probabilities {
+                ngrams[n - 1].insert(ngram, Entry { probability: probability.log10(), backoff: None });
+            }
+
+            if n > 1 {
+                for (context, (total, left)) in contexts {
+                    if let Some(entry) = ngrams[n - 2].get_mut(context) {
+                        entry.backoff = Some((left / total).log10());
+                    }
+                }
+            }
+        }
+
+        let mut model = LanguageModel { words: self.words, ids: self.ids, ngrams, followers: HashMap::new() };
+        model.index_followers();
+        model
+    }
+
+    /**
+     * Turn the counts into a Markov chain to sample sentences from.
+     */
+    pub fn generator(self, temperature: f64, seed: u64) -> Generator {
+        let mut continuations: HashMap<Vec<u32>, Vec<(u32, f64)>> = HashMap::new();
+        for ngrams in &self.counts {
+            for (ngram, &count) in ngrams.iter().filter(|(ngram, _)| ngram[ngram.len() - 1] != START_ID) {
+                let (context, word) = ngram.split_at(ngram.len() - 1);
+                continuations.entry(context.to_vec()).or_default().push((word[0], count as f64));
+            }
+        }
+        for words in continuations.values_mut() {
+            // the order of a HashMap is random, and the same seed must give the same text
+            words.sort_unstable_by_key(|(word, _)| *word);
+            // relative to the most frequent word, so that low temperatures cannot overflow
+            let most = words.iter().map(|(_, count)| *count).fold(0.0, f64::max);
+            for (_, weight) in words.iter_mut() {
+                *weight = (*weight / most).powf(1.0 / temperature);
+            }
+        }
+        Generator { order: self.order, words: self.words, continuations, rng: fastrand::Rng::with_seed(seed) }
+    }
+}
+
+/**
+ * Samples sentences from n-gram counts: each word is drawn from the words seen after the longest context (up to the
+ * order, less one, of the words before it) that was seen at all, in proportion to its count to the power of 1/temperature.
+ * A temperature below 1 favours the most frequent continuations, one above 1 flattens them.
+ */
+pub struct Generator {
+    order: usize,
+    words: Vec<String>,
+    // the words seen after each context, with their weights
+    continuations: HashMap<Vec<u32>, Vec<(u32, f64)>>,
+    rng: fastrand::Rng,
+}
+
+impl Generator {
+    pub fn sentence(&mut self) -> Vec<String> {
+        let mut sentence = vec![START_ID];
+        while sentence.len() <= MAX_SENTENCE_LENGTH {
+            let recent = &sentence[sentence.len().saturating_sub(self.order - 1)..];
+            let candidates = match (0..=recent.len()).find_map(|start| self.continuations.get(&recent[start..])) {
+                Some(candidates) => candidates,
+                None => break,
+            };
+
+            let mut target = self.rng.f64() * candidates.iter().map(|(_, weight)| weight).sum::<f64>();
+            let mut next = candidates[candidates.len() - 1].0;
+            for &(word, weight) in candidates {
+                if target < weight {
+                    next = word;
+                    break;
+                }
+                target -= weight;
+            }
+            if self.words[next as usize] == SENTENCE_END {
+                break;
+            }
+            sentence.push(next);
+        }
+        sentence[1..].iter().map(|&id| self.words[id as usize].clone()).collect()
+    }
+}
+
+/**
+ * The modified Kneser-Ney discounts for n-grams seen once, twice and three or more times,
+ * from how many n-grams have each adjusted count from 1 to 4.
+ */
+fn discounts(adjusted: &HashMap<Vec<u32>, u32>) -> [f64; 3] {
+    let mut having = [0.0; 5];
+    for &count in adjusted.values().filter(|&&count| count <= 4) {
+        having[count as usize] += 1.0;
+    }
+    if having[1..].contains(&0.0) {
+        return FALLBACK_DISCOUNTS;
+    }
+
+    let y = having[1] / (having[1] + 2.0 * having[2]);
+    let discounts = [
+        1.0 - 2.0 * y * having[2] / having[1],
+        2.0 - 3.0 * y * having[3] / having[2],
+        3.0 - 4.0 * y * having[4] / having[3],
+    ];
+    if discounts.iter().enumerate().all(|(i, &discount)| discount > 0.0 && discount < (i + 1) as f64) {
+        discounts
+    } else {
+        FALLBACK_DISCOUNTS
+    }
+}
+
+/**
+ * A word predicted to come next, with its probability.
+ */
+pub struct Prediction {
+    pub word: String,
+    pub probability: f64,
+}
+
+/**
+ * How well a model predicts some text: a sentence, a document or several.
+ */
+#[derive(Clone, Copy, Default)]
+pub struct Score {
+    pub sentences: usize,
+    pub words: usize,
+    pub oov: usize,
+    // log10 of the probability of the words in the vocabulary and of each end of sentence
+    pub log_probability: f64,
+}
+
+impl Score {
+    pub fn add(&mut self, other: &Score) {
+        self.sentences += other.sentences;
+        self.words += other.words;
+        self.oov += other.oov;
+        self.log_probability += other.log_probability;
+    }
+
+    /**
+     * The perplexity per predicted token: every word in the vocabulary and every end of sentence.
+     */
+    pub fn perplexity(&self) -> f64 {
+        let tokens = self.words - self.oov + self.sentences;
+        10f64.powf(-self.log_probability / tokens.max(1) as f64)
+    }
+
+    pub fn oov_rate(&self) -> f64 {
+        self.oov as f64 / self.words.max(1) as f64
+    }
+}
+
+#[derive(Clone)]
+struct Entry {
+    // log10 of the probability of the last word of the n-gram after the others
+    probability: f64,
+    // log10 of the weight of the lower-order distribution after the n-gram, if any word was seen after it
+    backoff: Option<f64>,
+}
+
+/**
+ * A backoff n-gram language model.
+ */
+pub struct LanguageModel {
+    words: Vec<String>,
+    ids: HashMap<String, u32>,
+    // ngrams[n - 1]: the probability and backoff weight of each n-gram, as word ids
+    ngrams: Vec<HashMap<Vec<u32>, Entry>>,
+    // the words the model has an n-gram for after each context (the empty context for unigrams), most probable first
+    followers: HashMap<Vec<u32>, Vec<(u32, f64)>>,
+}
+
+impl LanguageModel {
+    /**
+     * Load a model from an ARPA file, such as one written by `write_arpa` or by another toolkit.
+     */
+    pub fn load_arpa(file_path: &str) -> io::Result<LanguageModel> {
+        let reader = BufReader::new(File::open(file_path)?);
+        let invalid = |number: usize, message: String| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", number + 1, message));
+
+        let mut model = LanguageModel { words: Vec::new(), ids: HashMap::new(), ngrams: Vec::new(), followers: HashMap::new() };
+        // the order of the section being read, 0 before the first
+        let mut n = 0;
+        let mut ended = false;
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || ended {
+                continue;
+            }
+            if line == "\\end\\" {
+                ended = true;
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('\\').and_then(|line| line.strip_suffix("-grams:")) {
+                n = match section.parse::<usize>() {
+                    Ok(section) if section == model.ngrams.len() + 1 => section,
+                    _ => return Err(invalid(number, format!("unexpected section \"{}\"", line))),
+                };
+                model.ngrams.push(HashMap::new());
+                continue;
+            }
+            // the \data\ header, whose counts are not needed
+            if n == 0 {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let probability = fields[0].parse::<f64>().ok().filter(|_| fields.len() == n + 1 || fields.len() == n + 2);
+            let backoff = match fields.get(n + 1) {
+                Some(backoff) => backoff.parse::<f64>().ok(),
+                None => Some(0.0),
+            };
+            let (probability, backoff) = match (probability, backoff) {
+                (Some(probability), Some(backoff)) => (probability, backoff),
+                _ => return Err(invalid(number, format!("expected a probability, {} words and a backoff weight, found \"{}\"", n, line))),
+            };
+            let ngram: Vec<u32> = fields[1..=n].iter().map(|word| model.intern(word)).collect();
+            model.ngrams[n - 1].insert(ngram, Entry { probability, backoff: Some(backoff).filter(|_| fields.len() == n + 2) });
+        }
+
+        if model.ngrams.is_empty() || !ended {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not an ARPA file: missing n-gram sections or \\end\\"));
+        }
+        model.intern(UNKNOWN);
+        model.index_followers();
+        Ok(model)
+    }
+
+    fn index_followers(&mut self) {
+        let markers: Vec<u32> = [SENTENCE_START, SENTENCE_END, UNKNOWN].iter().filter_map(|marker| self.ids.get(*marker).copied()).collect();
+        for ngrams in &self.ngrams {
+            for (ngram, entry) in ngrams.iter().filter(|(ngram, _)| !markers.contains(&ngram[ngram.len() - 1])) {
+                let (context, word) = ngram.split_at(ngram.len() - 1);
+                self.followers.entry(context.to_vec()).or_default().push((word[0], entry.probability));
+            }
+        }
+        for followers in self.followers.values_mut() {
+            followers.sort_unstable_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        }
+    }
+
+    /**
+     * Get the `count` most probable next words after a prefix of words, most probable first, with their probabilities.
+     * The prefix is taken as part of a sentence, not its start; words missing from the vocabulary are read as <unk>.
+     * Markers (<s>, </s>, <unk>) are never predicted.
+     *
+     * A word the model has no n-gram for after the longest context gets the probability of the next shorter context
+     * times that context's backoff weight, so only the most probable words after each context need to be looked at.
+     */
+    pub fn predict(&self, prefix: &[&str], count: usize) -> Vec<Prediction> {
+        let unknown = self.ids.get(UNKNOWN).copied().unwrap_or(UNKNOWN_ID);
+        let ids: Vec<u32> = prefix.iter().map(|word| self.ids.get(*word).copied().unwrap_or(unknown)).collect();
+        let mut context = &ids[ids.len().saturating_sub(self.order() - 1)..];
+
+        let mut predictions: Vec<(u32, f64)> = Vec::new();
+        // every word with an n-gram after a longer context: its probability is that n-gram's, whether predicted or not
+        let mut explicit: HashSet<u32> = HashSet::new();
+        let mut backoff = 0.0;
+        loop {
+            if let Some(followers) = self.followers.get(context) {
+                predictions.extend(followers.iter()
+                    .filter(|(word, _)| !explicit.contains(word))
+                    .take(count)
+                    .map(|&(word, probability)| (word, backoff + probability)));
+                explicit.extend(followers.iter().map(|(word, _)| *word));
+            }
+            if context.is_empty() {
+                break;
+            }
+            backoff += self.ngrams[context.len() - 1].get(context).and_then(|entry| entry.backoff).unwrap_or(0.0);
+            context = &context[1..];
+        }
+
+        predictions.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        predictions.into_iter()
+            .take(count)
+            .map(|(word, probability)| Prediction { word: self.words[word as usize].clone(), probability: 10f64.powf(probability) })
+            .collect()
+    }
+
+    fn intern(&mut self, word: &str) -> u32 {
+        if let Some(id) = self.ids.get(word) {
+            return *id;
+        }
+        let id = self.words.len() as u32;
+        self.words.push(word.to_string());
+        self.ids.insert(word.to_string(), id);
+        id
+    }
+
+    /**
+     * Get the log10 probability of a word after a context (of word ids, oldest first), backing off to ever shorter
+     * contexts until the model knows the n-gram; a word missing from the vocabulary has the probability of <unk>.
+     */
+    fn log_probability(&self, context: &[u32], word: u32) -> f64 {
+        let mut context = &context[context.len().saturating_sub(self.order() - 1)..];
+        let mut backoff = 0.0;
+        loop {
+            let mut ngram = context.to_vec();
+            ngram.push(word);
+            if let Some(entry) = self.ngrams[context.len()].get(&ngram) {
+                return backoff + entry.probability;
+            }
+            if context.is_empty() {
+                let unknown = self.ids.get(UNKNOWN).and_then(|id| self.ngrams[0].get(&vec![*id]));
+                return backoff + unknown.map_or(NEVER, |entry| entry.probability);
+            }
+            backoff += self.ngrams[context.len() - 1].get(context).and_then(|entry| entry.backoff).unwrap_or(0.0);
+            context = &context[1..];
+        }
+    }
+
+    /**
+     * Score a sentence, including the end of sentence. Out-of-vocabulary words are counted but, as in SRILM,
+     * left out of the probability; they still stand in the context of the words after them, as <unk>.
+     */
+    pub fn score_sentence(&self, sentence: &[String]) -> Score {
+        let unknown = self.ids.get(UNKNOWN).copied().unwrap_or(UNKNOWN_ID);
+        let mut context = vec![self.ids.get(SENTENCE_START).copied().unwrap_or(START_ID)];
+        let mut score = Score { sentences: 1, words: sentence.len(), oov: 0, log_probability: 0.0 };
+
+        for word in sentence {
+            match self.ids.get(word.as_str()).filter(|id| self.ngrams[0].contains_key(&vec![**id])) {
+                Some(&id) => {
+                    score.log_probability += self.log_probability(&context, id);
+                    context.push(id);
+                }
+                None => {
+                    score.oov += 1;
+                    context.push(unknown);
+                }
+            }
+        }
+        if let Some(&end) = self.ids.get(SENTENCE_END) {
+            score.log_probability += self.log_probability(&context, end);
+        }
+        score
+    }
+
+    pub fn order(&self) -> usize {
+        self.ngrams.len()
+    }
+
+    pub fn vocabulary_size(&self) -> usize {
+        self.ngrams[0].len()
+    }
+
+    /**
+     * Write the model in the ARPA format read by SRILM, KenLM and most speech and translation toolkits.
+     */
+    pub fn write_arpa(&self, file_path: &str) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(file_path)?);
+        writeln!(file, "\\data\\")?;
+        for (n, ngrams) in self.ngrams.iter().enumerate() {
+            writeln!(file, "ngram {}={}", n + 1, ngrams.len())?;
+        }
+
+        for (n, ngrams) in self.ngrams.iter().enumerate() {
+            writeln!(file, "\n\\{}-grams:", n + 1)?;
+            let mut sorted: Vec<(Vec<&str>, &Entry)> = ngrams.iter()
+                .map(|(ngram, entry)| (ngram.iter().map(|&id| self.words[id as usize].as_str()).collect(), entry))
+                .collect();
+            sorted.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+            for (ngram, entry) in sorted {
+                match entry.backoff {
+                    Some(backoff) => writeln!(file, "{:.6}\t{}\t{:.6}", entry.probability, ngram.join(" "), backoff)?,
+                    None => writeln!(file, "{:.6}\t{}", entry.probability, ngram.join(" "))?,
+                }
+            }
+        }
+        writeln!(file, "\n\\end\\")?;
+        file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn train(order: usize) -> LanguageModel {
+        let mut counter = NgramCounter::new(order);
+        for sentence in ["the cat sat", "the dog sat", "a cat ran", "the cat ran away", "a dog sat down"] {
+            let words: Vec<String> = sentence.split(' ').map(str::to_string).collect();
+            counter.add_sentence(&words);
+        }
+        counter.train()
+    }
+
+    // the probability of every word the model can predict (all but <s>) after a context
+    fn total_probability(model: &LanguageModel, context: &[&str]) -> f64 {
+        let context: Vec<u32> = context.iter().map(|word| model.ids[*word]).collect();
+        model.ngrams[0].keys()
+            .filter(|unigram| unigram[0] != model.ids[SENTENCE_START])
+            .map(|unigram| 10f64.powf(model.log_probability(&context, unigram[0])))
+            .sum()
+    }
+
+    #[test]
+    fn distributions_sum_to_one() {
+        let model = train(3);
+        assert!((total_probability(&model, &[]) - 1.0).abs() < 1e-9);
+        assert!((total_probability(&model, &["the"]) - 1.0).abs() < 1e-9);
+        assert!((total_probability(&model, &["the", "cat"]) - 1.0).abs() < 1e-9);
+        assert!((total_probability(&model, &[SENTENCE_START]) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn predictions_keep_explicit_probabilities() {
+        // a backoff model (not Kneser-Ney) in which "b" is less probable after "a" than backing off would make it
+        let arpa = "\\data\\\nngram 1=5\nngram 2=3\n\n\\1-grams:\n-1.0\t</s>\n-99\t<s>\n-0.3\ta\t-0.30103\n-0.5\tb\n-0.6\tc\n\n\
+            \\2-grams:\n-0.2\ta a\n-1.0\ta c\n-2.0\ta b\n\n\\end\\\n";
+        let file_path = std::env::temp_dir().join(format!("lspt-hw2-backoff-{}.arpa", std::process::id()));
+        let file_path = file_path.to_str().unwrap();
+        std::fs::write(file_path, arpa).unwrap();
+        let model = LanguageModel::load_arpa(file_path).unwrap();
+        std::fs::remove_file(file_path).unwrap();
+
+        let predictions = model.predict(&["a"], 2);
+        let words: Vec<&str> = predictions.iter().map(|prediction| prediction.word.as_str()).collect();
+        assert_eq!(words, ["a", "c"]);
+        assert!((predictions[1].probability - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn arpa_round_trip() {
+        let model = train(3);
+        let file_path = std::env::temp_dir().join(format!("lspt-hw2-round-trip-{}.arpa", std::process::id()));
+        let file_path = file_path.to_str().unwrap();
+        model.write_arpa(file_path).unwrap();
+        let loaded = LanguageModel::load_arpa(file_path).unwrap();
+        std::fs::remove_file(file_path).unwrap();
+
+        assert_eq!(loaded.order(), model.order());
+        for (ngrams, loaded_ngrams) in model.ngrams.iter().zip(&loaded.ngrams) {
+            assert_eq!(ngrams.len(), loaded_ngrams.len());
+            for (ngram, entry) in ngrams {
+                let ngram: Vec<u32> = ngram.iter().map(|&id| loaded.ids[&model.words[id as usize]]).collect();
+                let loaded_entry = &loaded_ngrams[&ngram];
+                assert!((entry.probability - loaded_entry.probability).abs() < 1e-6);
+                match (entry.backoff, loaded_entry.backoff) {
+                    (Some(backoff), Some(loaded_backoff)) => assert!((backoff - loaded_backoff).abs() < 1e-6),
+                    (None, None) => {}
+                    _ => panic!("backoff weights differ"),
+                }
+            }
+        }
+
+        let sentence: Vec<String> = ["the", "dog", "ran"].iter().map(|word| word.to_string()).collect();
+        let (score, loaded_score) = (model.score_sentence(&sentence), loaded.score_sentence(&sentence));
+        assert!((score.log_probability - loaded_score.log_probability).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+/**
+ * A lemma table: the dictionary form of each inflected word it lists, such as "mice" -> "mouse".
+ */
+pub struct LemmaTable {
+    lemmas: HashMap<String, String>,
+}
+
+impl LemmaTable {
+    /**
+     * Load a lemma table from a file with one "form lemma" pair per line, separated by a tab or spaces,
+     * e.g. "were\tbe". Blank lines and lines starting with '#' are ignored. Words are matched in lowercase,
+     * as `clean` leaves them; if a form is listed twice, the last lemma given for it is used.
+     */
+    pub fn load(file_path: &str) -> io::Result<LemmaTable> {
+        let reader = BufReader::new(File::open(file_path)?);
+        let mut lemmas = HashMap::new();
+
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(form), Some(lemma), None) => {
+                    lemmas.insert(form.to_lowercase(), lemma.to_lowercase());
+                }
+                _ => return Err(io::Error::new(io::ErrorKind::InvalidData,
+                    format!("line {}: expected a word form and its lemma, found \"{}\"", number + 1, line))),
+            }
+        }
+        // a lemma is its own lemma, unless the table lists it as a form of something else
+        let lemma_forms: Vec<String> = lemmas.values().cloned().collect();
+        for lemma in lemma_forms {
+            lemmas.entry(lemma.clone()).or_insert(lemma);
+        }
+        Ok(LemmaTable { lemmas })
+    }
+
+    /**
+     * Get the lemma of a word, or None if the table does not list it.
+     */
+    pub fn lemma(&self, word: &str) -> Option<&str> {
+        self.lemmas.get(word).map(String::as_str)
+    }
+}
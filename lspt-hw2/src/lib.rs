@@ -0,0 +1,5 @@
+/*
+ * The parts of the word counter that are useful to other programs, such as a search box suggesting how to complete
+ * a phrase from a language model trained on a corpus.
+ */
+pub mod language_model;
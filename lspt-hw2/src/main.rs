@@ -2,10 +2,24 @@ use std::fs::File;
 use std::io::{self, BufRead, BufReader};
 use std::path::Path;
 use std::ffi::OsStr;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 
-/**
+mod language;
+mod lemmas;
+mod readers;
+mod regex_tokenizer;
+mod segmenter;
+mod spelling;
+mod stemming;
+mod stop_word_finder;
+mod truecase;
+mod xml;
+
+use lspt_hw2::language_model;
+use readers::{code, latex, mail, mediawiki, office, records, subtitle, tei, warc};
+
+/*
  * Refactor 1: Extract Function (n-grams)
  * Here, we extracted the bi-grams, and trigrams functions into a single function called get_ngram_occurrences
  * which takes in the words and n as arguments and returns the n-grams where each n gram is made up of n words.
@@ -27,7 +41,7 @@ use std::env;
  * Moved the print statements into a separate function called printing_occurrences
  */
 
-const STOP_WORDS: &'static[&'static str] = &[
+const STOP_WORDS: &[&str] = &[
     "the", "of", "to", "a", "and", "in", "said", "for", "that", "was", 
     "on", "he", "is", "with", "at", "by", "it", "from", "as", "be", 
     "were", "an", "have", "his", "but", "has", "are", "not", "who", 
@@ -36,30 +50,190 @@ const STOP_WORDS: &'static[&'static str] = &[
     "us", "percent", "up", "one", "people",
 ];
 
+/**
+ * Whether a word is one of `stop_words` (usually `STOP_WORDS`), in any case.
+ */
+fn is_stop_word(word: &str, stop_words: &[&str]) -> bool {
+    stop_words.iter().any(|stop_word| stop_word.eq_ignore_ascii_case(word))
+}
+
+
+/**
+ * Which n-grams containing stop words (or words too short to count) are left out.
+ */
+#[derive(Clone, Copy, PartialEq)]
+enum StopWordPolicy {
+    // reject an n-gram with a stop word anywhere in it
+    Any,
+    // reject an n-gram that starts or ends with a stop word, keeping "ministry of truth" and "war is peace"
+    Edges,
+    // reject only n-grams made entirely of stop words
+    All,
+    // keep every n-gram
+    None,
+}
+
+impl StopWordPolicy {
+    fn parse(name: &str) -> Result<StopWordPolicy, String> {
+        match name {
+            "any" => Ok(StopWordPolicy::Any),
+            "edges" => Ok(StopWordPolicy::Edges),
+            "all" => Ok(StopWordPolicy::All),
+            "none" => Ok(StopWordPolicy::None),
+            _ => Err(format!("invalid stop word policy \"{}\" (expected any, edges, all or none)", name)),
+        }
+    }
+
+    /**
+     * Whether an n-gram is left out, given which of its words are stop words.
+     */
+    fn rejects(&self, stopped: &[bool]) -> bool {
+        match self {
+            StopWordPolicy::Any => stopped.iter().any(|&stopped| stopped),
+            StopWordPolicy::Edges => stopped.first() == Some(&true) || stopped.last() == Some(&true),
+            StopWordPolicy::All => stopped.iter().all(|&stopped| stopped),
+            StopWordPolicy::None => false,
+        }
+    }
+}
+
+/**
+ * The stop word policy for each n-gram length.
+ */
+struct StopWordPolicies {
+    // for lengths without a policy of their own
+    default: StopWordPolicy,
+    lengths: HashMap<usize, StopWordPolicy>,
+}
+
+impl StopWordPolicies {
+    /**
+     * Parse a comma-separated list of policies, each for every length ("edges") or for some lengths ("3-5=edges").
+     * Later entries override earlier ones.
+     */
+    fn parse(value: &str) -> Result<StopWordPolicies, String> {
+        let mut policies = StopWordPolicies { default: StopWordPolicy::Any, lengths: HashMap::new() };
+        for part in value.split(',') {
+            match part.split_once('=') {
+                Some((lengths, policy)) => {
+                    let policy = StopWordPolicy::parse(policy.trim())?;
+                    for n in parse_lengths(lengths)? {
+                        policies.lengths.insert(n, policy);
+                    }
+                }
+                None => {
+                    policies.default = StopWordPolicy::parse(part.trim())?;
+                    policies.lengths.clear();
+                }
+            }
+        }
+        Ok(policies)
+    }
+
+    fn get(&self, n: usize) -> StopWordPolicy {
+        self.lengths.get(&n).copied().unwrap_or(self.default)
+    }
+}
+
+/**
+ * How the case of words is treated.
+ */
+#[derive(Clone, Copy, PartialEq)]
+enum Case {
+    // everything is lowercased
+    Lower,
+    // words are counted as written, so "Big Brother" and "big brother" are different
+    Preserve,
+    // as written, except that sentence-initial words are lowercased when they are usually lowercase elsewhere
+    Truecase,
+}
+
+/**
+ * Which characters `clean` keeps as part of a word, and how short a word may be before it is ignored.
+ * The default keeps letters and at most one apostrophe per word, and ignores words shorter than 2.
+ */
+#[derive(Clone)]
+struct TokenizerConfig {
+    // keep hyphenated compounds such as "well-known" as one word
+    hyphens: bool,
+    // keep numbers (including decimal points and thousands separators) as words
+    numbers: bool,
+    // allow more than one apostrophe in a word, as in "rock'n'roll"
+    multiple_apostrophes: bool,
+    // treat the typographic apostrophe (’) as an apostrophe rather than a separator
+    curly_apostrophes: bool,
+    // words shorter than this are not counted
+    min_length: usize,
+    case: Case,
+    // keep letters outside ASCII (accented letters, other alphabets) rather than treating them as separators
+    unicode: bool,
+    // a user-defined tokenizer that replaces `clean`'s rules (hyphens, numbers and apostrophes) entirely
+    regex: Option<regex_tokenizer::RegexTokenizer>,
+    // splits Chinese, Japanese and Thai text into words; without it `clean` drops such text
+    segmenter: Option<segmenter::Segmenter>,
+    // mark where each sentence ends with `SENTENCE_END`, for the language model
+    sentence_ends: bool,
+}
+
+impl Default for TokenizerConfig {
+    fn default() -> TokenizerConfig {
+        TokenizerConfig { hyphens: false, numbers: false, multiple_apostrophes: false, curly_apostrophes: false, min_length: 2, case: Case::Lower, unicode: false, regex: None, segmenter: None, sentence_ends: false }
+    }
+}
+
+impl TokenizerConfig {
+    /**
+     * Whether a word is too short to count.
+     * A CJK ideograph counts as two characters, since one is often a whole word.
+     */
+    fn is_short(&self, word: &str) -> bool {
+        word.chars().map(|c| if segmenter::is_ideograph(c) { 2 } else { 1 }).sum::<usize>() < self.min_length
+    }
+}
 
 /**
  * Sanitize a line of text and prepare it for further processing.
- * This function turns all non-alpha characters into whitespace except for the first apostrophe in a word.
- * All alpha characters are returned as alphabetic.
+ * This function turns all non-alpha characters into whitespace except for the first apostrophe in a word
+ * (and, as `config` allows, further apostrophes, hyphens inside words and numbers).
+ * All alpha characters are returned as alphabetic, lowercased unless `config` preserves case.
  */
-fn clean(check: String) -> String {
-    let mut check_iter: String = check.to_lowercase().chars()
+fn clean(check: String, config: &TokenizerConfig) -> String {
+    let check = if config.case == Case::Lower { check.to_lowercase() } else { check };
+    let mut check_iter: String = check.chars()
         .filter(|&c| c != '\n' && c != '\t' && c != '\r' && c != '«' && c != '»' && c != '×')
         .collect();
 
-    check_iter = check_iter.replace(|c: char| !c.is_ascii(), " ");
+    if config.curly_apostrophes {
+        check_iter = check_iter.replace('\u{2019}', "'");
+    }
+    if !config.unicode {
+        check_iter = check_iter.replace(|c: char| !c.is_ascii(), " ");
+    }
+
+    let is_word_char = |c: char| c.is_alphabetic() || (config.numbers && c.is_ascii_digit());
 
     // parse entire line
     let mut apostrophe_count: i32 = 0;
     let mut in_word: bool = false;
     let mut last_apostrophe: bool = false;
+    // characters are only ever replaced by as many bytes of whitespace, so positions in the original still hold
+    let original = check_iter.clone();
+    let bytes = original.as_bytes();
 
-    for (i, c) in check_iter.clone().char_indices() {
-        if !char::is_alphabetic(c) { 
-            if c == '\'' && apostrophe_count == 0 && in_word {
-                // allow only one apostrophe 
+    for (i, c) in original.char_indices() {
+        let next = original[i + c.len_utf8()..].chars().next();
+        let next_is_word_char = next.is_some_and(is_word_char);
+
+        if !is_word_char(c) { 
+            if c == '\'' && in_word && !last_apostrophe && (apostrophe_count == 0 || config.multiple_apostrophes) {
+                // allow only one apostrophe, unless configured otherwise
                 apostrophe_count += 1;
                 last_apostrophe = true;
+            } else if c == '-' && config.hyphens && in_word && !last_apostrophe && next_is_word_char {
+                // a hyphen joining two parts of a compound
+            } else if (c == '.' || c == ',') && config.numbers && i > 0 && bytes[i - 1].is_ascii_digit()
+                && next.is_some_and(|next| next.is_ascii_digit()) {
+                // a decimal point or thousands separator
             } else {
                 // turn all other non-alphabetical characters or additional apostrophes into whitespace
                 if last_apostrophe {
@@ -68,7 +242,7 @@ fn clean(check: String) -> String {
                 }
                 apostrophe_count = 0;
                 in_word = false;
-                check_iter.replace_range(i..i+1, " ");
+                check_iter.replace_range(i..i + c.len_utf8(), &" ".repeat(c.len_utf8()));
             }
         } else {
             if last_apostrophe {
@@ -93,85 +267,632 @@ fn get_file_extension(filename: &str) -> Option<&str> {
 }
 
 /**
- * Get a list of each word from a file.
- * This method returns words that are processed to be entirely in lowercase, with at most one apostrophe.
+ * Get the lines of a plain text file, to be split into words by `get_words`.
+ */
+fn read_lines_from_file(file_path: &str) -> io::Result<Vec<String>> {
+    let path = Path::new(file_path);
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+    reader.lines().collect()
+}
+
+/**
+ * Get a list of each word from lines of text that have already been pulled out of a document.
+ * By default, words are processed to be entirely in lowercase, with at most one apostrophe.
  * All other characters are treated as delimeters, and are filtered out.
  * 
  * For example, the sequence: "I'm...a word?" will yield the words ["i'm", "a", "word"].
  * 
  * Notably, stop words and short words are not filtered out.
+ * A "line" may itself contain line breaks; `clean` drops those, so they are split on first to keep words apart.
+ * When truecasing, capitalized words that start a sentence are marked with `truecase::SENTENCE_START`.
  */
-fn read_words_from_file(file_path: &str) -> io::Result<Vec<String>> {
-    let path = Path::new(file_path);
-    let file = File::open(&path)?;
-    let reader = BufReader::new(file);
+fn get_words(lines: Vec<String>, config: &TokenizerConfig) -> Vec<String> {
     let mut words = Vec::new();
+    // the first word, and the first after a sentence end or a blank line, starts a sentence
+    let mut sentence_start = true;
 
     // Process each word, line by line, then add to word list.
-    for line in reader.lines() {
-        let line = line?;
-        for word in clean(line).split_whitespace().collect::<Vec<&str>>() {
-            if word.len() == 0 {
-                continue;
+    for line in lines.iter().flat_map(|line| line.lines()) {
+        if config.case != Case::Truecase && !config.sentence_ends {
+            words.extend(get_tokens(line, config));
+            continue;
+        }
+
+        if line.trim().is_empty() {
+            sentence_start = true;
+            end_sentence(&mut words, config);
+        }
+        for (sentence, ends) in truecase::split_sentences(line) {
+            for word in get_tokens(sentence, config) {
+                if config.case == Case::Truecase && sentence_start && word.starts_with(char::is_uppercase) {
+                    words.push(format!("{}{}", truecase::SENTENCE_START, word));
+                } else {
+                    words.push(word);
+                }
+                sentence_start = false;
+            }
+            if ends {
+                end_sentence(&mut words, config);
             }
+            sentence_start |= ends;
+        }
+    }
+    words
+}
+
+/**
+ * Mark the end of a sentence when sentence ends are wanted, unless no words were added since the last one.
+ */
+fn end_sentence(words: &mut Vec<String>, config: &TokenizerConfig) {
+    if config.sentence_ends && words.last().is_some_and(|word| word != SENTENCE_END) {
+        words.push(SENTENCE_END.to_string());
+    }
+}
+
+/**
+ * Split a piece of text into words with the user's regex tokenizer if there is one, or with `clean` otherwise.
+ * When segmenting, runs of Chinese, Japanese and Thai are handed to the segmenter instead.
+ */
+fn get_tokens(text: &str, config: &TokenizerConfig) -> Vec<String> {
+    let segmenter = match &config.segmenter {
+        Some(segmenter) => segmenter,
+        None => return get_spaced_tokens(text, config),
+    };
 
-            words.push(word.to_string());
+    let mut tokens = Vec::new();
+    for (run, unspaced) in segmenter::split_runs(text) {
+        if unspaced {
+            tokens.extend(segmenter.segment(run));
+        } else {
+            tokens.extend(get_spaced_tokens(run, config));
         }
     }
-    Ok(words)
+    tokens
 }
 
-// current version
-fn get_ngram_occurrences(words: &Vec<String>, n: i32) -> io::Result<Vec<(String, i32)>> {
-    let mut ngram_count = HashMap::new();
+fn get_spaced_tokens(text: &str, config: &TokenizerConfig) -> Vec<String> {
+    match &config.regex {
+        Some(tokenizer) => tokenizer.tokens(text).into_iter()
+            .map(|token| if config.case == Case::Lower { token.to_lowercase() } else { token })
+            .collect(),
+        None => clean(text.to_string(), config).split_whitespace().map(String::from).collect(),
+    }
+}
 
-    if words.len() == 0 {
-        return Ok(Vec::new());
+// a word of its own marking the end of a sentence, for counting n-grams within sentences
+const SENTENCE_END: &str = "\u{2}";
+
+/**
+ * A run of words that n-grams are not allowed to span, such as a single subtitle cue.
+ * Formats without such boundaries give one segment for the whole document.
+ * `start` is the time in seconds at which the segment begins, for formats that are timed.
+ */
+struct Segment {
+    words: Vec<String>,
+    start: Option<f64>,
+}
+
+impl Segment {
+    /**
+     * Get the sentences of the segment, when its sentence ends are marked.
+     */
+    fn sentences(&self) -> impl Iterator<Item = &[String]> {
+        self.words.split(|word| word == SENTENCE_END).filter(|sentence| !sentence.is_empty())
     }
+}
 
-    for i in 0..words.len() - (n-1) as usize {
-        let mut ngram = String::new();
-        let mut bad: bool = false;
-        for j in 0..n {
-            if bad {
-                break;
-            }
-            if words[i+j as usize].len() < 2 {
-                bad = true;
-                break;
+/**
+ * One document to be counted: usually a whole file, but formats that hold many records can give one per record.
+ * `name` identifies the document in reports and errors.
+ */
+struct Document {
+    name: String,
+    segments: Vec<Segment>,
+    // when detecting languages, the language of the document, if there was enough text to tell
+    language: Option<language::Language>,
+}
+
+/**
+ * Make a document from the text of each of its segments, given as lines and a start time, by splitting it into words.
+ * When detecting languages, the language is detected from the text before it is split, and with --auto-language a
+ * document in any language but English is split with the tokenizer that keeps every alphabet.
+ */
+fn get_document(name: String, texts: Vec<(Vec<String>, Option<f64>)>, options: &Options) -> Document {
+    let language = options.language_detector.as_ref()
+        .and_then(|detector| detector.detect(texts.iter().flat_map(|(lines, _)| lines)));
+    let config = match &options.language_tokenizer {
+        Some(language_tokenizer) if language.is_none_or(|language| language.code != "eng") => language_tokenizer,
+        _ => &options.tokenizer,
+    };
+
+    let segments = texts.into_iter().map(|(lines, start)| Segment { words: get_words(lines, config), start }).collect();
+    Document { name, segments, language }
+}
+
+/**
+ * Read the documents in a file of any supported filetype, handing each one to `add_document` as it is read.
+ * Formats that can hold a great many documents (wiki dumps) are streamed, so the file never has to fit in memory.
+ * Returns None if the filetype is not supported.
+ */
+fn read_documents(file_path: &str, options: &Options, add_document: &mut dyn FnMut(Document) -> io::Result<()>) -> Option<io::Result<()>> {
+    let mut add_all = |documents: io::Result<Vec<Document>>| -> io::Result<()> {
+        for document in documents? {
+            add_document(document)?;
+        }
+        Ok(())
+    };
+    let whole = |texts: Vec<(Vec<String>, Option<f64>)>| vec![get_document(file_path.to_string(), texts, options)];
+    let untimed = |lines: io::Result<Vec<String>>| lines.map(|lines| whole(vec![(lines, None)]));
+    let timed = |cues: io::Result<Vec<subtitle::Cue>>| cues.map(|cues| whole(cues.into_iter()
+        .map(|cue| (cue.lines, Some(cue.start)))
+        .collect()));
+    let records = |records: io::Result<Vec<records::Record>>| records
+        .map(|records| get_record_documents(file_path, records, options));
+
+    match get_file_extension(file_path)? {
+        "txt" => Some(add_all(untimed(read_lines_from_file(file_path)))),
+        "docx" => Some(add_all(untimed(office::read_docx(file_path)))),
+        "odt" => Some(add_all(untimed(office::read_odt(file_path)))),
+        "srt" => Some(add_all(timed(subtitle::read_srt(file_path)))),
+        "vtt" => Some(add_all(timed(subtitle::read_vtt(file_path)))),
+        "jsonl" => Some(add_all(records(records::read_jsonl(file_path, &options.text_field)))),
+        "csv" => Some(add_all(records(records::read_csv(file_path, &options.column, ',')))),
+        "tsv" => Some(add_all(records(records::read_csv(file_path, &options.column, '\t')))),
+        "eml" => Some(add_all(untimed(mail::read_eml(file_path, &options.mail)))),
+        "tex" => Some(add_all(untimed(latex::read_latex(file_path)))),
+        "mbox" => Some(add_all(mail::read_mbox(file_path, &options.mail).map(|messages| messages.into_iter()
+            .map(|message| get_document(format!("{}:{}", file_path, message.line), vec![(message.lines, None)], options))
+            .collect()))),
+        "xml" | "tei" | "bz2" if mediawiki::is_dump(file_path).unwrap_or(false) => {
+            Some(mediawiki::read_dump(file_path, &mut |article| add_document(get_document(article.title, vec![(article.lines, None)], options))))
+        }
+        "bz2" => Some(Err(io::Error::new(io::ErrorKind::InvalidData, "only MediaWiki dumps can be read bzip2-compressed"))),
+        "warc" | "gz" if file_path.ends_with(".warc") || file_path.ends_with(".warc.gz") => {
+            Some(warc::read_warc(file_path, &mut |page| add_document(get_document(page.uri, vec![(page.lines, None)], options))))
+        }
+        extension if options.code && code::get_language(extension).is_some() => {
+            let language = code::get_language(extension)?;
+            Some(add_all(code::read_code(file_path, language, options.code_strings).map(|comments| whole(comments.into_iter()
+                .map(|comment| (vec![comment], None))
+                .collect()))))
+        }
+        "xml" | "tei" => Some(add_all(tei::read_xml(file_path, &options.xml).map(|documents| documents.into_iter()
+            .map(|document| get_document(document.name, document.segments.into_iter().map(|text| (vec![text], None)).collect(), options))
+            .collect()))),
+        _ => None,
+    }
+}
+
+/**
+ * Turn the records of a JSON Lines or CSV file into documents.
+ * Each record is its own segment; with --record-documents each record is also its own document, named by its line number.
+ */
+fn get_record_documents(file_path: &str, records: Vec<records::Record>, options: &Options) -> Vec<Document> {
+    if options.record_documents {
+        records.into_iter()
+            .map(|record| get_document(format!("{}:{}", file_path, record.line), vec![(vec![record.text], None)], options))
+            .collect()
+    } else {
+        vec![get_document(file_path.to_string(), records.into_iter().map(|record| (vec![record.text], None)).collect(), options)]
+    }
+}
+
+/**
+ * Everything counted so far across all documents.
+ * Documents are added one at a time and only their counts are kept, so memory use does not grow with corpus size
+ * beyond the number of distinct words and n-grams.
+ */
+struct Corpus {
+    valid_documents: i32,
+    word_count: usize,
+    word_occurrences: HashMap<String, i32>,
+    bigram_occurrences: HashMap<String, i32>,
+    trigram_occurrences: HashMap<String, i32>,
+    quadgram_occurrences: HashMap<String, i32>,
+    pentagram_occurrences: HashMap<String, i32>,
+    // per-window word counts of each timed document, when --time-window is given
+    time_windows: Vec<(String, TimeWindows)>,
+    // when --stem is given, words are counted by their stem
+    stemming: Option<stemming::Stemming>,
+    // when --lemmas is given, words are counted by their lemma
+    lemmas: Option<lemmas::LemmaTable>,
+    // when truecasing, decides the case of sentence-initial words; set once the corpus has been observed
+    truecaser: Option<truecase::Truecaser>,
+    // for each length given with --char-ngrams, the character n-grams of the words
+    char_ngram_occurrences: Vec<(usize, HashMap<String, i32>)>,
+    // for each length given with --skip-lengths, the n-grams with gaps of up to --skip words
+    skip_gram_occurrences: Vec<(usize, HashMap<String, i32>)>,
+    // when --detect-language is given, the detected language of each document
+    languages: Vec<(String, String)>,
+    // when suggesting spelling corrections, the count of each pair of adjacent words, as "first second"
+    adjacent_pairs: Option<HashMap<String, i32>>,
+    // when --arpa is given, the n-grams of each sentence, to train a language model from
+    ngram_counter: Option<language_model::NgramCounter>,
+}
+
+impl Corpus {
+    fn new(options: &Options) -> io::Result<Corpus> {
+        let lemmas = match &options.lemma_table {
+            Some(file_path) => Some(lemmas::LemmaTable::load(file_path)
+                .map_err(|e| io::Error::new(e.kind(), format!("cannot read lemma table \"{}\": {}", file_path, e)))?),
+            None => None,
+        };
+
+        Ok(Corpus {
+            valid_documents: 0,
+            word_count: 0,
+            word_occurrences: HashMap::new(),
+            bigram_occurrences: HashMap::new(),
+            trigram_occurrences: HashMap::new(),
+            quadgram_occurrences: HashMap::new(),
+            pentagram_occurrences: HashMap::new(),
+            time_windows: Vec::new(),
+            stemming: options.stem.map(stemming::Stemming::new),
+            lemmas,
+            truecaser: None,
+            char_ngram_occurrences: options.char_ngrams.iter().map(|&n| (n, HashMap::new())).collect(),
+            skip_gram_occurrences: match options.skip {
+                Some(_) => options.skip_lengths.iter().map(|&n| (n, HashMap::new())).collect(),
+                None => Vec::new(),
+            },
+            languages: Vec::new(),
+            adjacent_pairs: if options.misspellings || !options.spell.is_empty() { Some(HashMap::new()) } else { None },
+            ngram_counter: options.arpa.as_ref().map(|_| language_model::NgramCounter::new(options.lm_order)),
+        })
+    }
+
+    /**
+     * Get the form a word is counted as: its lemma if `lemmatize` is set and the lemma table lists it, otherwise
+     * its stem when stemming, otherwise the word itself. Stop words and short words are only ever lemmatized,
+     * so they are still recognised.
+     */
+    fn normalize(&mut self, word: &str, config: &TokenizerConfig, stop_words: &[&str], lemmatize: bool) -> String {
+        let lemma = self.lemmas.as_ref().filter(|_| lemmatize).and_then(|lemmas| lemmas.lemma(word));
+        let stemming = match &mut self.stemming {
+            Some(stemming) => stemming,
+            None => return lemma.unwrap_or(word).to_string(),
+        };
+
+        let mut normalized = word.to_string();
+        match lemma {
+            Some(lemma) => {
+                normalized = lemma.to_string();
+                stemming.keep(lemma);
             }
-            ngram.push_str(&words[i+j as usize]);
-            ngram.push_str(" ");
+            None if !config.is_short(word) && !is_stop_word(word, stop_words) => stemming.stem(&mut normalized),
+            None => stemming.keep(word),
         }
+        normalized
+    }
 
-        if bad {
-            continue;
+    /**
+     * Count a document. Its n-grams and time windows are filtered with `stop_words`, unless its language has its own.
+     */
+    fn add_document(&mut self, mut document: Document, options: &Options, stop_words: &[&str]) -> io::Result<()> {
+        if let Some(truecaser) = &self.truecaser {
+            for segment in &mut document.segments {
+                truecaser.resolve(&mut segment.words);
+            }
         }
 
-        ngram.pop();
+        if options.tokenizer.sentence_ends {
+            for segment in &mut document.segments {
+                if let Some(counter) = &mut self.ngram_counter {
+                    for sentence in segment.sentences() {
+                        counter.add_sentence(sentence);
+                    }
+                }
+                segment.words.retain(|word| word != SENTENCE_END);
+            }
+        }
 
-        for word in STOP_WORDS {
-            if bad {
-                break;
+        let mut stop_words = stop_words;
+        if options.detect_language {
+            let language = document.language;
+            // English keeps the usual list, so results only change for documents in other languages
+            if let Some(language_stop_words) = language.filter(|language| language.code != "eng").and_then(|language| language.stop_words()) {
+                if options.auto_language && !options.apply_corpus_stop_words {
+                    stop_words = language_stop_words;
+                }
             }
-            for j in 0..n {
-                if  words[i+j as usize] == *word {
-                    bad = true;
-                    break;
+            let label = language.map_or("unknown".to_string(), |language| format!("{} ({})", language.name, language.code));
+            self.languages.push((document.name.to_string(), label));
+        }
+
+        let normalizing = self.lemmas.is_some() || self.stemming.is_some();
+        // a lemma table only applies to n-grams when asked to; stems always do
+        let lemmatize_ngrams = self.lemmas.is_none() || options.lemmatize_ngrams;
+
+        for segment in &mut document.segments {
+            if let Some(adjacent_pairs) = &mut self.adjacent_pairs {
+                for pair in segment.words.windows(2) {
+                    let count = adjacent_pairs.entry(pair.join(" ")).or_insert(0);
+                    *count += 1;
                 }
             }
+            for (n, occurrences) in &mut self.char_ngram_occurrences {
+                add_char_ngram_occurrences(&segment.words, *n, options.char_padding, occurrences);
+            }
+
+            if normalizing {
+                let normalized: Vec<String> = segment.words.iter().map(|word| self.normalize(word, &options.tokenizer, stop_words, true)).collect();
+                let ngram_words: Vec<String> = segment.words.iter().zip(&normalized)
+                    .map(|(word, normalized)| {
+                        // stop words keep their own form in n-grams, so n-grams containing them are still rejected
+                        if options.tokenizer.is_short(word) || is_stop_word(word, stop_words) {
+                            return word.to_string();
+                        }
+                        let lemmatized = self.lemmas.as_ref().is_some_and(|lemmas| lemmas.lemma(word).is_some());
+                        if lemmatize_ngrams || !lemmatized {
+                            normalized.to_string()
+                        } else {
+                            // counted by its lemma as a word, but only stemmed in n-grams
+                            self.normalize(word, &options.tokenizer, stop_words, false)
+                        }
+                    })
+                    .collect();
+                self.add_ngrams(&ngram_words, options, stop_words)?;
+                segment.words = normalized;
+            } else {
+                self.add_ngrams(&segment.words, options, stop_words)?;
+            }
+
+            let file_filtered_words = segment.words.iter().filter(|w| !options.tokenizer.is_short(w)).collect::<Vec<&String>>();
+
+            for word in file_filtered_words {
+                self.word_count += 1;
+                let cnt = self.word_occurrences.entry(word.to_string()).or_insert(0);
+                *cnt+= 1;
+            }
+        }
+
+        if let Some(window) = options.time_window {
+            if document.segments.iter().any(|segment| segment.start.is_some()) {
+                self.time_windows.push((document.name, get_time_window_occurrences(&document.segments, window, &options.tokenizer, stop_words)));
+            }
         }
-        if bad {
+
+        self.valid_documents += 1;
+        Ok(())
+    }
+
+    fn add_ngrams(&mut self, words: &[String], options: &Options, stop_words: &[&str]) -> io::Result<()> {
+        let config = &options.tokenizer;
+        let policies = &options.stop_word_policies;
+        let file_bigram_occurrences = get_ngram_occurrences(words, 2, config, stop_words, policies.get(2))?;
+        let file_trigram_occurrences = get_ngram_occurrences(words, 3, config, stop_words, policies.get(3))?;
+        let file_quadgram_occurrences = get_ngram_occurrences(words, 4, config, stop_words, policies.get(4))?;
+        let file_pentagram_occurrences = get_ngram_occurrences(words, 5, config, stop_words, policies.get(5))?;
+
+        for (key, value) in file_bigram_occurrences {
+            let count = self.bigram_occurrences.entry(key).or_insert(0);
+            *count+=value;
+        }
+
+        for (key, value) in file_trigram_occurrences {
+            let count = self.trigram_occurrences.entry(key).or_insert(0);
+            *count+=value;
+        }
+        for (key, value) in file_quadgram_occurrences {
+            let count = self.quadgram_occurrences.entry(key).or_insert(0);
+            *count+=value;
+        }
+        for (key, value) in file_pentagram_occurrences {
+            let count = self.pentagram_occurrences.entry(key).or_insert(0);
+            *count+=value;
+        }
+        if let Some(skip) = options.skip {
+            for (n, occurrences) in &mut self.skip_gram_occurrences {
+                add_skip_gram_occurrences(words, *n, skip, config, stop_words, policies.get(*n), occurrences);
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * When counting stems, replace every stem in the counts by its report label (see `Stemming::label`).
+     */
+    fn label_stems(&mut self) {
+        let stemming = match &self.stemming {
+            Some(stemming) => stemming,
+            None => return,
+        };
+        let relabel = |occurrences: &mut HashMap<String, i32>| {
+            *occurrences = occurrences.drain().map(|(stems, count)| (stemming.label(&stems), count)).collect();
+        };
+
+        relabel(&mut self.word_occurrences);
+        relabel(&mut self.bigram_occurrences);
+        relabel(&mut self.trigram_occurrences);
+        relabel(&mut self.quadgram_occurrences);
+        relabel(&mut self.pentagram_occurrences);
+        for (_, occurrences) in &mut self.skip_gram_occurrences {
+            relabel(occurrences);
+        }
+        for (_, windows) in &mut self.time_windows {
+            for occurrences in windows.values_mut() {
+                relabel(occurrences);
+            }
+        }
+    }
+}
+
+// current version
+fn get_ngram_occurrences(words: &[String], n: i32, config: &TokenizerConfig, stop_words: &[&str], policy: StopWordPolicy) -> io::Result<Vec<(String, i32)>> {
+    let mut ngram_count = HashMap::new();
+
+    if words.len() < n as usize {
+        return Ok(Vec::new());
+    }
+
+    for i in 0..words.len() - (n-1) as usize {
+        let ngram_words = &words[i..i + n as usize];
+        // short words are treated like stop words
+        let stopped: Vec<bool> = ngram_words.iter()
+            .map(|word| config.is_short(word) || is_stop_word(word, stop_words))
+            .collect();
+        if policy.rejects(&stopped) {
             continue;
         }
-        let count = ngram_count.entry(ngram).or_insert(0);
+
+        let count = ngram_count.entry(ngram_words.join(" ")).or_insert(0);
         *count += 1;
     }
 
     Ok(ngram_count.into_iter().map(|(bigram, count)| (bigram.clone(), count)).collect())
 }
 
-fn printing_occurrences(valid_documents: i32, words: Vec<String>, word_occurrences: HashMap<String, i32>, bigram_occurrences: HashMap<String, i32>, trigram_occurrences: HashMap<String, i32>, quadgram_occurrences: HashMap<String, i32>, pentagram_occurrences: HashMap<String, i32>) {
+/**
+ * Count the k-skip-n-grams of a segment's words that are not contiguous: n words in order with up to `skip` words
+ * skipped in total between them, such as "big _ brother" for "big brother" with one word skipped.
+ * Each skipped word is shown as '_'. The words taken are filtered by the stop word policy for n-grams of this length,
+ * as contiguous n-grams are; the skipped words can be anything.
+ */
+fn add_skip_gram_occurrences(words: &[String], n: usize, skip: usize, config: &TokenizerConfig, stop_words: &[&str], policy: StopWordPolicy, occurrences: &mut HashMap<String, i32>) {
+    let stopped: Vec<bool> = words.iter()
+        .map(|word| config.is_short(word) || is_stop_word(word, stop_words))
+        .collect();
+
+    // each partial skip-gram is the positions of its words so far
+    let mut partial: Vec<Vec<usize>> = (0..words.len()).map(|i| vec![i]).collect();
+    while let Some(positions) = partial.pop() {
+        let first = positions[0];
+        let last = positions[positions.len() - 1];
+        if positions.len() == n {
+            let taken: Vec<bool> = positions.iter().map(|&i| stopped[i]).collect();
+            if last - first + 1 > n && !policy.rejects(&taken) {
+                let mut skip_gram = words[first].clone();
+                for pair in positions.windows(2) {
+                    for _ in pair[0] + 1..pair[1] {
+                        skip_gram.push_str(" _");
+                    }
+                    skip_gram.push(' ');
+                    skip_gram.push_str(&words[pair[1]]);
+                }
+                let count = occurrences.entry(skip_gram).or_insert(0);
+                *count += 1;
+            }
+            continue;
+        }
+
+        let skipped = last - first + 1 - positions.len();
+        for next in (last + 1..words.len()).take(skip - skipped + 1) {
+            let mut extended = positions.clone();
+            extended.push(next);
+            partial.push(extended);
+        }
+    }
+}
+
+fn printing_skip_gram_occurrences(n: usize, skip: usize, occurrences: HashMap<String, i32>) {
+    let total: i32 = occurrences.values().sum();
+    println!("\nNumber of \"interesting\" {}-skip-{}-grams: {}", skip, n, total);
+    println!("Number of unique \"interesting\" {}-skip-{}-grams: {}\n", skip, n, occurrences.len());
+
+    let mut sorted: Vec<_> = occurrences.into_iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    match sorted.len() {
+        1 => println!("Top 1 \"interesting\" {}-skip-{}-gram:", skip, n),
+        2..=31 => println!("Top {} \"interesting\" {}-skip-{}-grams:", sorted.len(), skip, n),
+        _ => println!("Top 32 \"interesting\" {}-skip-{}-grams:", skip, n),
+    }
+
+    for (skip_gram, count) in sorted.iter().take(32) {
+        println!("{} {}", count, skip_gram);
+    }
+}
+
+/**
+ * Count the character n-grams of each word. With `padded`, words are padded with '_' so that n-grams
+ * at the start and end of words (like "_th" and "ng_") are told apart from those inside them.
+ */
+fn add_char_ngram_occurrences(words: &[String], n: usize, padded: bool, occurrences: &mut HashMap<String, i32>) {
+    for word in words {
+        let mut chars: Vec<char> = word.chars().collect();
+        if padded {
+            chars.insert(0, '_');
+            chars.push('_');
+        }
+
+        for ngram in chars.windows(n) {
+            let count = occurrences.entry(ngram.iter().collect()).or_insert(0);
+            *count += 1;
+        }
+    }
+}
+
+fn printing_corpus_stop_words(candidates: Vec<stop_word_finder::Candidate>) {
+    match candidates.len() {
+        1 => println!("\nTop 1 corpus stop word (occurrences, share of {}-word parts it occurs in):", stop_word_finder::PART_SIZE),
+        n => println!("\nTop {} corpus stop words (occurrences, share of {}-word parts they occur in):", n, stop_word_finder::PART_SIZE),
+    }
+
+    for candidate in candidates {
+        println!("{} {:.2} {}", candidate.count, candidate.dispersion, candidate.word);
+    }
+}
+
+fn printing_misspellings(misspellings: Vec<(String, i32, spelling::Correction)>) {
+    println!("\nNumber of likely misspellings: {}\n", misspellings.len());
+    match misspellings.len() {
+        0 => return,
+        1 => println!("Top 1 likely misspelling (count, word, correction and its count):"),
+        2..=63 => println!("Top {} likely misspellings (count, word, correction and its count):", misspellings.len()),
+        _ => println!("Top 64 likely misspellings (count, word, correction and its count):"),
+    }
+
+    for (word, count, correction) in misspellings.iter().take(64) {
+        println!("{} {} -> {} {}", count, word, correction.word, correction.count);
+    }
+}
+
+/**
+ * Print the best corrections of each word of the text that looks misspelled, ranked using the words around it.
+ */
+fn printing_spelling_suggestions(speller: &spelling::Speller, text: &str, config: &TokenizerConfig) {
+    println!("\nSpelling suggestions for \"{}\":", text);
+    let words = without_truecase_marks(&get_words(vec![text.to_string()], config));
+    let mut any = false;
+    for (i, word) in words.iter().enumerate().filter(|(_, word)| !config.is_short(word)) {
+        let before: Vec<&str> = i.checked_sub(1).map(|i| words[i].as_str()).into_iter().collect();
+        let after: Vec<&str> = words.get(i + 1).map(String::as_str).into_iter().collect();
+        let corrections = speller.corrections(word, &before, &after);
+        if corrections.is_empty() {
+            continue;
+        }
+        let corrections: Vec<String> = corrections.iter().take(5)
+            .map(|correction| format!("{} ({}, {})", correction.word, correction.distance, correction.count))
+            .collect();
+        println!("{}: {}", word, corrections.join(", "));
+        any = true;
+    }
+    if !any {
+        println!("no corrections");
+    }
+}
+
+fn printing_char_ngram_occurrences(n: usize, occurrences: HashMap<String, i32>) {
+    let total: i32 = occurrences.values().sum();
+    println!("\nNumber of character {}-grams: {}", n, total);
+    println!("Number of unique character {}-grams: {}\n", n, occurrences.len());
+
+    let mut sorted: Vec<_> = occurrences.into_iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    match sorted.len() {
+        1 => println!("Top 1 character {}-gram:", n),
+        2..=31 => println!("Top {} character {}-grams:", sorted.len(), n),
+        _ => println!("Top 32 character {}-grams:", n),
+    }
+
+    for (ngram, count) in sorted.iter().take(32) {
+        println!("{} {}", count, ngram);
+    }
+}
+
+fn printing_occurrences(valid_documents: i32, word_count: usize, word_occurrences: HashMap<String, i32>, bigram_occurrences: HashMap<String, i32>, trigram_occurrences: HashMap<String, i32>, quadgram_occurrences: HashMap<String, i32>, pentagram_occurrences: HashMap<String, i32>) {
     let mut bigram_count: i32 = 0;
     let mut trigram_count: i32 = 0;
     let mut quadgram_count: i32 = 0;
@@ -191,7 +912,7 @@ fn printing_occurrences(valid_documents: i32, words: Vec<String>, word_occurrenc
         pentagram_count += count;
     }
     println!("Number of valid documents: {}", valid_documents);
-    println!("Number of words: {}", words.len());
+    println!("Number of words: {}", word_count);
     println!("Number of unique words: {}", word_occurrences.len());
     println!("Number of \"interesting\" bigrams: {}", bigram_count);
     println!("Number of unique \"interesting\" bigrams: {}", bigram_occurrences.len());
@@ -227,7 +948,7 @@ fn printing_occurrences(valid_documents: i32, words: Vec<String>, word_occurrenc
     for (key, value) in words_sorted.iter().take(128) {
         println!("{} {}", value, key);
     } 
-    println!(""); 
+    println!(); 
 
     match bigram_sorted.len() {
         1 => println!("Top 1 interesting bigram:"),
@@ -238,7 +959,7 @@ fn printing_occurrences(valid_documents: i32, words: Vec<String>, word_occurrenc
     for (bigram, count) in bigram_sorted.iter().take(64) {
         println!("{} {}", count, bigram);
     }
-    println!("");   
+    println!();   
 
     match trigram_sorted.len() {
         1 => println!("Top 1 interesting trigram:"),
@@ -249,7 +970,7 @@ fn printing_occurrences(valid_documents: i32, words: Vec<String>, word_occurrenc
     for(trigram, count) in trigram_sorted.iter().take(32) {
         println!("{} {}", count, trigram);
     } 
-    println!("");
+    println!();
     
     match quadgram_sorted.len() {
         1 => println!("Top 1 interesting 4-gram:"),
@@ -260,7 +981,7 @@ fn printing_occurrences(valid_documents: i32, words: Vec<String>, word_occurrenc
     for(quadgram, count) in quadgram_sorted.iter().take(16) {
         println!("{} {}", count, quadgram);
     }
-    println!("");
+    println!();
 
     match pentagram_sorted.len() {
         1 => println!("Top 1 interesting 5-gram:"),
@@ -274,75 +995,646 @@ fn printing_occurrences(valid_documents: i32, words: Vec<String>, word_occurrenc
 }
 
 
+// the counts of the words in each time window that has any segment, by the window's number counting from zero
+type TimeWindows = BTreeMap<u64, HashMap<String, i32>>;
+
+/**
+ * Count the words of each time window of a timed document, so that spikes of a term can be located.
+ * Windows are `window` seconds long and start at zero; a segment belongs to the window it starts in.
+ * Stop words and short words are not counted.
+ */
+fn get_time_window_occurrences(segments: &[Segment], window: f64, config: &TokenizerConfig, stop_words: &[&str]) -> TimeWindows {
+    let mut windows: TimeWindows = BTreeMap::new();
+
+    for segment in segments {
+        let start = match segment.start {
+            Some(start) => start,
+            None => continue,
+        };
+        let occurrences = windows.entry((start / window).floor() as u64).or_default();
+
+        for word in segment.words.iter().filter(|w| !config.is_short(w) && !is_stop_word(w, stop_words)) {
+            let count = occurrences.entry(word.to_string()).or_insert(0);
+            *count += 1;
+        }
+    }
+    windows
+}
+
+fn format_time(seconds: f64) -> String {
+    let seconds = seconds as i64;
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+fn printing_time_windows(file_path: &str, window: f64, windows: TimeWindows) {
+    println!("\nWord frequency per {} second window in \"{}\":", window, file_path);
+
+    // windows without any counted words are left out
+    for (index, occurrences) in windows.into_iter().filter(|(_, occurrences)| !occurrences.is_empty()) {
+        let start = index as f64 * window;
+        let total: i32 = occurrences.values().sum();
+        let mut sorted: Vec<_> = occurrences.into_iter().collect();
+        sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let top: Vec<String> = sorted.iter().take(8).map(|(word, count)| format!("{} {}", count, word)).collect();
+        println!("{} - {} ({} words): {}", format_time(start), format_time(start + window), total, top.join(", "));
+    }
+}
+
+/**
+ * What the program does with the files it reads.
+ */
+#[derive(PartialEq)]
+enum Mode {
+    // count words and n-grams
+    Count,
+    // report how well a language model predicts each sentence and document
+    Score,
+    // print text sampled from the n-gram counts
+    Generate,
+    // print the most probable next words after each prefix given, according to a language model
+    Predict,
+}
+
+/**
+ * Command line options. Every argument that is not an option is a file to read.
+ * The first argument may name a mode other than counting: "score", "generate" or "predict".
+ * In predict mode, the other arguments are prefixes rather than files.
+ */
+struct Options {
+    mode: Mode,
+    files: Vec<String>,
+    // language model to use in score and predict modes, as an ARPA file
+    model: Option<String>,
+    // report word frequency per window of this many seconds for timed documents (subtitles)
+    time_window: Option<f64>,
+    // JSON pointer to the text of each JSON Lines record
+    text_field: String,
+    // column holding the text of each CSV/TSV row
+    column: records::Column,
+    // count each JSON Lines/CSV record as a document of its own
+    record_documents: bool,
+    // quoted text and signatures to leave out of email messages
+    mail: mail::MailOptions,
+    // which elements of an XML file to count, and which to treat as documents
+    xml: tei::XmlSelection,
+    // read source code files (by extension), counting only their comments and docstrings
+    code: bool,
+    // with `code`, count string literals too
+    code_strings: bool,
+    // count words by their stem, using the Snowball stemmer for this language
+    stem: Option<rust_stemmers::Algorithm>,
+    // file of word forms and their lemmas to count words by
+    lemma_table: Option<String>,
+    // count n-grams by lemmas too, not just single words
+    lemmatize_ngrams: bool,
+    // how lines are split into words
+    tokenizer: TokenizerConfig,
+    // lengths of character n-grams to count
+    char_ngrams: Vec<usize>,
+    // pad words with '_' before taking their character n-grams
+    char_padding: bool,
+    // which n-grams with stop words in them are left out, per length
+    stop_word_policies: StopWordPolicies,
+    // also count n-grams with gaps of up to this many words
+    skip: Option<usize>,
+    // lengths of the n-grams counted with gaps
+    skip_lengths: Vec<usize>,
+    // train a language model and write it to this ARPA file
+    arpa: Option<String>,
+    // the order of the language model, and of the n-grams generated text is sampled from
+    lm_order: usize,
+    // in generate mode, the seed of the random number generator, so the same text can be generated again
+    seed: Option<u64>,
+    // in generate mode, how far to flatten (above 1) or sharpen (below 1) the distribution of the next word
+    temperature: f64,
+    // in generate mode, the number of words to generate, rounded up to whole sentences
+    length: usize,
+    // in predict mode, the number of next words to give for each prefix
+    predictions: usize,
+    // report the rare words of the corpus that look like misspellings of frequent ones
+    misspellings: bool,
+    // text to suggest spelling corrections for, from the words of the corpus
+    spell: Vec<String>,
+    // the largest edit distance between a misspelling and its corrections
+    max_edit_distance: usize,
+    // find this many stop words from the corpus and report them
+    corpus_stop_words: Option<usize>,
+    // filter with the stop words found in the corpus instead of the built-in list
+    apply_corpus_stop_words: bool,
+    // detect and report the language of each document
+    detect_language: bool,
+    language_detector: Option<language::LanguageDetector>,
+    // use the stop words of each document's detected language, and split documents not in English with `language_tokenizer`
+    auto_language: bool,
+    // the tokenizer with letters of every alphabet kept and the scripts written without spaces segmented
+    language_tokenizer: Option<TokenizerConfig>,
+}
+
+// the number of stop words found when they are applied without saying how many
+const DEFAULT_CORPUS_STOP_WORDS: usize = 100;
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        mode: Mode::Count,
+        files: Vec::new(),
+        model: None,
+        time_window: None,
+        text_field: "text".to_string(),
+        column: records::Column::Name("text".to_string()),
+        record_documents: false,
+        mail: mail::MailOptions { strip_quotes: false, strip_signatures: false },
+        xml: tei::XmlSelection { select: None, exclude: Vec::new(), split: None },
+        code: false,
+        code_strings: false,
+        stem: None,
+        lemma_table: None,
+        lemmatize_ngrams: false,
+        tokenizer: TokenizerConfig::default(),
+        char_ngrams: Vec::new(),
+        char_padding: false,
+        stop_word_policies: StopWordPolicies { default: StopWordPolicy::Any, lengths: HashMap::new() },
+        skip: None,
+        skip_lengths: vec![2],
+        arpa: None,
+        lm_order: 3,
+        seed: None,
+        temperature: 1.0,
+        length: 100,
+        predictions: 10,
+        misspellings: false,
+        spell: Vec::new(),
+        max_edit_distance: 2,
+        corpus_stop_words: None,
+        apply_corpus_stop_words: false,
+        detect_language: false,
+        language_detector: None,
+        auto_language: false,
+        language_tokenizer: None,
+    };
+    let mut args = args.iter().peekable();
+    match args.peek().map(|arg| arg.as_str()) {
+        Some("score") => options.mode = Mode::Score,
+        Some("generate") => options.mode = Mode::Generate,
+        Some("predict") => options.mode = Mode::Predict,
+        _ => {}
+    }
+    if options.mode != Mode::Count {
+        args.next();
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--time-window" => {
+                let value = args.next().ok_or("--time-window needs a number of seconds")?;
+                match value.parse::<f64>() {
+                    Ok(seconds) if seconds > 0.0 => options.time_window = Some(seconds),
+                    _ => return Err(format!("invalid --time-window \"{}\"", value)),
+                }
+            }
+            "--text-field" => options.text_field = args.next().ok_or("--text-field needs a JSON pointer")?.to_string(),
+            "--column" => options.column = records::Column::parse(args.next().ok_or("--column needs a column number or name")?),
+            "--record-documents" => options.record_documents = true,
+            "--strip-quotes" => options.mail.strip_quotes = true,
+            "--strip-signatures" => options.mail.strip_signatures = true,
+            "--select" => options.xml.select = Some(tei::ElementPath::parse(args.next().ok_or("--select needs an element path")?)?),
+            "--exclude" => options.xml.exclude = args.next().ok_or("--exclude needs element names")?
+                .split(',').map(|name| name.trim().to_string()).filter(|name| !name.is_empty()).collect(),
+            "--code" => options.code = true,
+            "--code-strings" => {
+                options.code = true;
+                options.code_strings = true;
+            }
+            "--stem" => options.stem = Some(stemming::parse_algorithm(args.next().ok_or("--stem needs a language")?)?),
+            "--lemmas" => options.lemma_table = Some(args.next().ok_or("--lemmas needs a lemma table file")?.to_string()),
+            "--lemmatize-ngrams" => options.lemmatize_ngrams = true,
+            "--keep-hyphens" => options.tokenizer.hyphens = true,
+            "--keep-numbers" => options.tokenizer.numbers = true,
+            "--multiple-apostrophes" => options.tokenizer.multiple_apostrophes = true,
+            "--curly-apostrophes" => options.tokenizer.curly_apostrophes = true,
+            "--min-length" => {
+                let value = args.next().ok_or("--min-length needs a number of characters")?;
+                options.tokenizer.min_length = value.parse().map_err(|_| format!("invalid --min-length \"{}\"", value))?;
+            }
+            "--case" => options.tokenizer.case = match args.next().ok_or("--case needs lower, preserve or truecase")?.as_str() {
+                "lower" => Case::Lower,
+                "preserve" => Case::Preserve,
+                "truecase" => Case::Truecase,
+                value => return Err(format!("invalid --case \"{}\"", value)),
+            },
+            "--tokenizer" => options.tokenizer.regex = Some(regex_tokenizer::RegexTokenizer::load(
+                args.next().ok_or("--tokenizer needs a tokenizer config file")?)?),
+            "--segment" => {
+                if options.tokenizer.segmenter.is_none() {
+                    options.tokenizer.segmenter = Some(segmenter::Segmenter::bigrams());
+                }
+            }
+            "--lexicon" => options.tokenizer.segmenter = Some(segmenter::Segmenter::load(
+                args.next().ok_or("--lexicon needs a lexicon file")?)?),
+            "--char-ngrams" => options.char_ngrams = parse_lengths(args.next().ok_or("--char-ngrams needs a length")?)?,
+            "--char-padding" => options.char_padding = true,
+            "--stop-word-policy" => options.stop_word_policies = StopWordPolicies::parse(
+                args.next().ok_or("--stop-word-policy needs a policy")?)?,
+            "--skip" => {
+                let value = args.next().ok_or("--skip needs a number of words")?;
+                match value.parse::<usize>() {
+                    Ok(skip) if skip > 0 => options.skip = Some(skip),
+                    _ => return Err(format!("invalid --skip \"{}\"", value)),
+                }
+            }
+            "--skip-lengths" => {
+                options.skip_lengths = parse_lengths(args.next().ok_or("--skip-lengths needs a length")?)?;
+                if options.skip_lengths[0] < 2 {
+                    return Err("--skip-lengths must be at least 2".to_string());
+                }
+            }
+            "--unicode" => options.tokenizer.unicode = true,
+            "--model" => options.model = Some(args.next().ok_or("--model needs a language model file")?.to_string()),
+            "--arpa" => options.arpa = Some(args.next().ok_or("--arpa needs a file to write the language model to")?.to_string()),
+            "--lm-order" => {
+                let value = args.next().ok_or("--lm-order needs an order")?;
+                match value.parse::<usize>() {
+                    Ok(order) if order > 0 => options.lm_order = order,
+                    _ => return Err(format!("invalid --lm-order \"{}\"", value)),
+                }
+            }
+            "--seed" => {
+                let value = args.next().ok_or("--seed needs a number")?;
+                options.seed = Some(value.parse().map_err(|_| format!("invalid --seed \"{}\"", value))?);
+            }
+            "--temperature" => {
+                let value = args.next().ok_or("--temperature needs a number")?;
+                match value.parse::<f64>() {
+                    Ok(temperature) if temperature > 0.0 && temperature.is_finite() => options.temperature = temperature,
+                    _ => return Err(format!("invalid --temperature \"{}\"", value)),
+                }
+            }
+            "--length" => {
+                let value = args.next().ok_or("--length needs a number of words")?;
+                options.length = value.parse().map_err(|_| format!("invalid --length \"{}\"", value))?;
+            }
+            "--predictions" => {
+                let value = args.next().ok_or("--predictions needs a number of words")?;
+                options.predictions = value.parse().map_err(|_| format!("invalid --predictions \"{}\"", value))?;
+            }
+            "--misspellings" => options.misspellings = true,
+            "--spell" => options.spell.push(args.next().ok_or("--spell needs text to correct")?.to_string()),
+            "--max-edit-distance" => {
+                let value = args.next().ok_or("--max-edit-distance needs a number of edits")?;
+                match value.parse::<usize>() {
+                    Ok(distance) if distance > 0 => options.max_edit_distance = distance,
+                    _ => return Err(format!("invalid --max-edit-distance \"{}\"", value)),
+                }
+            }
+            "--corpus-stop-words" => {
+                let value = args.next().ok_or("--corpus-stop-words needs a number of words")?;
+                match value.parse::<usize>() {
+                    Ok(count) if count > 0 => options.corpus_stop_words = Some(count),
+                    _ => return Err(format!("invalid --corpus-stop-words \"{}\"", value)),
+                }
+            }
+            "--apply-corpus-stop-words" => options.apply_corpus_stop_words = true,
+            "--detect-language" => options.detect_language = true,
+            "--auto-language" => {
+                options.detect_language = true;
+                options.auto_language = true;
+            }
+            "--split" => options.xml.split = Some(tei::ElementPath::parse(args.next().ok_or("--split needs an element path")?)?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ => options.files.push(arg.to_string()),
+        }
+    }
+
+    if (options.misspellings || !options.spell.is_empty()) && (options.stem.is_some() || options.lemma_table.is_some()) {
+        // corrections have to be words as written, not stems or lemmas
+        return Err("spelling suggestions cannot be combined with --stem or --lemmas".to_string());
+    }
+    if (options.mode == Mode::Score || options.mode == Mode::Predict) && options.model.is_none() {
+        return Err("score and predict need a language model: --model FILE".to_string());
+    }
+    if options.arpa.is_some() || options.mode == Mode::Score || options.mode == Mode::Generate {
+        options.tokenizer.sentence_ends = true;
+    }
+    if options.apply_corpus_stop_words && options.corpus_stop_words.is_none() {
+        options.corpus_stop_words = Some(DEFAULT_CORPUS_STOP_WORDS);
+    }
+    if options.detect_language {
+        options.language_detector = Some(language::LanguageDetector::new());
+    }
+    if options.auto_language {
+        let mut language_tokenizer = options.tokenizer.clone();
+        language_tokenizer.unicode = true;
+        if language_tokenizer.segmenter.is_none() {
+            language_tokenizer.segmenter = Some(segmenter::Segmenter::bigrams());
+        }
+        options.language_tokenizer = Some(language_tokenizer);
+    }
+    Ok(options)
+}
+
+/**
+ * Parse a list of n-gram lengths: a single length ("3"), a range ("2-4") or a comma-separated list of either.
+ */
+fn parse_lengths(value: &str) -> Result<Vec<usize>, String> {
+    let mut lengths = Vec::new();
+    for part in value.split(',') {
+        let (from, to) = part.split_once('-').unwrap_or((part, part));
+        match (from.trim().parse::<usize>(), to.trim().parse::<usize>()) {
+            (Ok(from), Ok(to)) if from >= 1 && from <= to => lengths.extend(from..=to),
+            _ => return Err(format!("invalid n-gram length \"{}\"", part)),
+        }
+    }
+    lengths.sort_unstable();
+    lengths.dedup();
+    Ok(lengths)
+}
+
+/**
+ * Get the words of a sentence as written. Modes other than counting skip the first pass of truecasing,
+ * so sentence-initial words are taken as written.
+ */
+fn without_truecase_marks(sentence: &[String]) -> Vec<String> {
+    sentence.iter().map(|word| word.trim_start_matches(truecase::SENTENCE_START).to_string()).collect()
+}
+
+/**
+ * Generate mode: print sentences sampled from the n-gram counts of the files, one per line,
+ * until there are at least as many words as asked for.
+ */
+fn generate(options: &Options) {
+    let mut counter = language_model::NgramCounter::new(options.lm_order);
+    for file_path in &options.files {
+        if !(Path::new(file_path).exists()) {
+            eprintln!("ERROR: cannot access \"{}\"", file_path);
+            continue;
+        }
+
+        let result = read_documents(file_path, options, &mut |document| {
+            for segment in &document.segments {
+                for sentence in segment.sentences() {
+                    counter.add_sentence(&without_truecase_marks(sentence));
+                }
+            }
+            Ok(())
+        });
+        match result {
+            Some(Ok(())) => {}
+            Some(Err(e)) => eprintln!("ERROR: cannot read \"{}\": {}", file_path, e),
+            None => eprintln!("ERROR: {} has unsupported filetype", file_path),
+        }
+    }
+
+    let mut generator = counter.generator(options.temperature, options.seed.unwrap_or_else(|| fastrand::u64(..)));
+    let mut length = 0;
+    while length < options.length {
+        let sentence = generator.sentence();
+        if sentence.is_empty() {
+            // nothing was read, or the files only hold empty sentences
+            break;
+        }
+        length += sentence.len();
+        println!("{}", sentence.join(" "));
+    }
+}
+
+/**
+ * Predict mode: print the most probable next words after each prefix, tokenized as the corpus was.
+ * The prefixes are the arguments, or else the lines of standard input, so that another program can keep asking.
+ */
+fn predict(options: &Options) -> io::Result<()> {
+    let model = load_model(options);
+    let answer = |prefix: &str| {
+        let words = without_truecase_marks(&get_words(vec![prefix.to_string()], &options.tokenizer));
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        println!("{}:", prefix);
+        for prediction in model.predict(&words, options.predictions) {
+            println!("{:.6} {}", prediction.probability, prediction.word);
+        }
+    };
+
+    if !options.files.is_empty() {
+        for prefix in &options.files {
+            answer(prefix);
+        }
+        return Ok(());
+    }
+    for line in io::stdin().lock().lines() {
+        answer(&line?);
+    }
+    Ok(())
+}
+
+fn load_model(options: &Options) -> language_model::LanguageModel {
+    let file_path = options.model.as_deref().unwrap_or_default();
+    match language_model::LanguageModel::load_arpa(file_path) {
+        Ok(model) => model,
+        Err(e) => {
+            eprintln!("ERROR: cannot read language model \"{}\": {}", file_path, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/**
+ * Score mode: print the perplexity and out-of-vocabulary rate of every sentence and document under the language model,
+ * then of all of them together.
+ */
+fn score(options: &Options) {
+    let model = load_model(options);
+
+    let mut total = language_model::Score::default();
+    let mut documents = 0;
+    for file_path in &options.files {
+        if !(Path::new(file_path).exists()) {
+            eprintln!("ERROR: cannot access \"{}\"", file_path);
+            continue;
+        }
+
+        let result = read_documents(file_path, options, &mut |document| {
+            let mut document_score = language_model::Score::default();
+            let mut sentences = Vec::new();
+            for segment in &document.segments {
+                for sentence in segment.sentences() {
+                    let sentence = without_truecase_marks(sentence);
+                    let sentence_score = model.score_sentence(&sentence);
+                    document_score.add(&sentence_score);
+                    sentences.push((sentence_score, sentence.join(" ")));
+                }
+            }
+
+            println!("\n{}: {} sentences, {} words, {} OOV ({:.2}%), perplexity {:.2}", document.name, document_score.sentences,
+                document_score.words, document_score.oov, document_score.oov_rate() * 100.0, document_score.perplexity());
+            println!("Perplexity, OOV/words and text of each sentence:");
+            for (sentence_score, text) in sentences {
+                println!("{:.2} {}/{} {}", sentence_score.perplexity(), sentence_score.oov, sentence_score.words, text);
+            }
+            total.add(&document_score);
+            documents += 1;
+            Ok(())
+        });
+        match result {
+            Some(Ok(())) => {}
+            Some(Err(e)) => eprintln!("ERROR: cannot read \"{}\": {}", file_path, e),
+            None => eprintln!("ERROR: {} has unsupported filetype", file_path),
+        }
+    }
+
+    println!("\nAll {} documents: {} sentences, {} words, {} OOV ({:.2}%), perplexity {:.2}", documents, total.sentences,
+        total.words, total.oov, total.oov_rate() * 100.0, total.perplexity());
+}
+
 fn main() -> io::Result<()> {
     let args: Vec<String> = env::args().collect();
-    let mut valid_documents: i32 = 0;
-    
-    if args.len() < 2 {
+
+    let options = match parse_args(&args[1..]) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if options.files.is_empty() && options.mode != Mode::Predict {
         eprintln!("ERROR: too few arguments");
         std::process::exit(1);
     }
 
-    // variables for word content
-    let mut words: Vec<String> = Vec::new();
-    let mut word_occurrences: HashMap<String, i32> = HashMap::new();
-    let mut bigram_occurrences: HashMap<String, i32> = HashMap::new();
-    let mut trigram_occurrences: HashMap<String, i32> = HashMap::new();
-    let mut quadgram_occurrences: HashMap<String, i32> = HashMap::new();
-    let mut pentagram_occurrences: HashMap<String, i32> = HashMap::new();
+    match options.mode {
+        Mode::Score => {
+            score(&options);
+            return Ok(());
+        }
+        Mode::Generate => {
+            generate(&options);
+            return Ok(());
+        }
+        Mode::Predict => return predict(&options),
+        Mode::Count => {}
+    }
 
-    for i in 1..args.len() {
-        let file_path = &args[i];
+    let mut corpus = match Corpus::new(&options) {
+        Ok(corpus) => corpus,
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let truecasing = options.tokenizer.case == Case::Truecase;
+    let mut corpus_stop_words = Vec::new();
+    if truecasing || options.corpus_stop_words.is_some() {
+        // a first pass learns how each word is usually written and which words are everywhere;
+        // errors are reported by the second
+        let mut truecaser = truecase::Truecaser::new();
+        let mut stop_word_finder = stop_word_finder::StopWordFinder::new();
+        for file_path in options.files.iter().filter(|file_path| Path::new(file_path).exists()) {
+            read_documents(file_path, &options, &mut |document| {
+                for segment in &document.segments {
+                    truecaser.observe(&segment.words);
+                }
+                stop_word_finder.add_document(document.segments.iter()
+                    .flat_map(|segment| segment.words.iter())
+                    .filter(|word| *word != SENTENCE_END)
+                    .filter(|word| !options.tokenizer.is_short(word.trim_start_matches(truecase::SENTENCE_START))));
+                Ok(())
+            });
+        }
+        if truecasing {
+            corpus.truecaser = Some(truecaser);
+        }
+        if let Some(count) = options.corpus_stop_words {
+            corpus_stop_words = stop_word_finder.candidates(count);
+        }
+    }
+    let stop_words: Vec<&str> = if options.apply_corpus_stop_words {
+        corpus_stop_words.iter().map(|candidate| candidate.word.as_str()).collect()
+    } else {
+        STOP_WORDS.to_vec()
+    };
 
+    for file_path in &options.files {
         // check if path exists
         if !(Path::new(file_path).exists()) {
             eprintln!("ERROR: cannot access \"{}\"", file_path);
             continue;
         }
 
-        let file_extension = get_file_extension(&file_path).unwrap();
-
-        if !(file_extension == "txt") {
-            eprintln!("ERROR: {} has unsupported filetype", file_path);
-            continue;
+        match read_documents(file_path, &options, &mut |document| corpus.add_document(document, &options, &stop_words)) {
+            Some(Ok(())) => {}
+            Some(Err(e)) => eprintln!("ERROR: cannot read \"{}\": {}", file_path, e),
+            None => eprintln!("ERROR: {} has unsupported filetype", file_path),
         }
+    }
+    corpus.label_stems();
+    let speller = corpus.adjacent_pairs.take()
+        .map(|adjacent_pairs| spelling::Speller::new(corpus.word_occurrences.clone(), adjacent_pairs, options.max_edit_distance));
+    printing_occurrences(corpus.valid_documents, corpus.word_count, corpus.word_occurrences, corpus.bigram_occurrences,
+        corpus.trigram_occurrences, corpus.quadgram_occurrences, corpus.pentagram_occurrences);
 
-        let file_words = read_words_from_file(file_path)?; // get raw words
-        let file_filtered_words = file_words.iter().filter(|w| w.len() >= 2).collect::<Vec<&String>>();
+    for (n, occurrences) in corpus.char_ngram_occurrences {
+        printing_char_ngram_occurrences(n, occurrences);
+    }
 
-        for word in file_filtered_words.clone() {
-            words.push(word.to_string());
-            let cnt = word_occurrences.entry(word.to_string()).or_insert(0);
-            *cnt+= 1;
+    if let Some(skip) = options.skip {
+        for (n, occurrences) in corpus.skip_gram_occurrences {
+            printing_skip_gram_occurrences(n, skip, occurrences);
         }
+    }
 
-        let file_bigram_occurrences = get_ngram_occurrences(&file_words, 2)?;
-        let file_trigram_occurrences = get_ngram_occurrences(&file_words, 3)?;
-        let file_quadgram_occurrences = get_ngram_occurrences(&file_words, 4)?;
-        let file_pentagram_occurrences = get_ngram_occurrences(&file_words, 5)?;
+    if options.corpus_stop_words.is_some() {
+        printing_corpus_stop_words(corpus_stop_words);
+    }
 
-        for (key, value) in file_bigram_occurrences.clone() {
-            let count = bigram_occurrences.entry(key).or_insert(0);
-            *count+=value;
+    if let Some(speller) = &speller {
+        if options.misspellings {
+            printing_misspellings(speller.misspellings());
         }
-
-        for (key, value) in file_trigram_occurrences.clone() {
-            let count = trigram_occurrences.entry(key).or_insert(0);
-            *count+=value;
+        for text in &options.spell {
+            printing_spelling_suggestions(speller, text, &options.tokenizer);
         }
-        for (key, value) in file_quadgram_occurrences.clone() {
-            let count = quadgram_occurrences.entry(key).or_insert(0);
-            *count+=value;
+    }
+
+    if let (Some(counter), Some(file_path)) = (corpus.ngram_counter, &options.arpa) {
+        let model = counter.train();
+        match model.write_arpa(file_path) {
+            Ok(()) => println!("\nLanguage model of order {} over {} words written to {}", model.order(), model.vocabulary_size(), file_path),
+            Err(e) => eprintln!("ERROR: cannot write language model \"{}\": {}", file_path, e),
         }
-        for (key, value) in file_pentagram_occurrences.clone() {
-            let count = pentagram_occurrences.entry(key).or_insert(0);
-            *count+=value;
+    }
+
+    if options.detect_language {
+        println!("\nLanguage of each document:");
+        for (name, language) in corpus.languages {
+            println!("{}: {}", name, language);
         }
+    }
 
-        valid_documents += 1;
+    for (name, windows) in corpus.time_windows {
+        printing_time_windows(&name, options.time_window.unwrap_or_default(), windows);
     }
-    printing_occurrences(valid_documents, words, word_occurrences, bigram_occurrences, trigram_occurrences, quadgram_occurrences, pentagram_occurrences);
-    
 
     Ok(()) 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str, config: &TokenizerConfig) -> Vec<String> {
+        clean(text.to_string(), config).split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn default_token_rules() {
+        let config = TokenizerConfig::default();
+        assert_eq!(words("I'm a well-known rock'n'roll fan, 'quoted' 3.14 don\u{2019}t", &config),
+            ["i'm", "a", "well", "known", "rock'n", "roll", "fan", "quoted", "don", "t"]);
+    }
+
+    #[test]
+    fn configured_token_rules() {
+        let config = TokenizerConfig { hyphens: true, numbers: true, multiple_apostrophes: true, curly_apostrophes: true, ..TokenizerConfig::default() };
+        assert_eq!(words("A well-known rock'n'roll fan- paid 1,000.50 for -it- in 2024. Don\u{2019}t", &config),
+            ["a", "well-known", "rock'n'roll", "fan", "paid", "1,000.50", "for", "it", "in", "2024", "don't"]);
+    }
+}
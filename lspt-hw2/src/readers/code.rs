@@ -0,0 +1,263 @@
+use std::fs;
+use std::io;
+
+/**
+ * The comment and string syntax of a family of programming languages.
+ */
+#[derive(Clone, Copy, PartialEq)]
+pub enum Language {
+    // Rust, C, C++, Java, Go, JavaScript/TypeScript: // and /* */ comments
+    CStyle { nested_comments: bool, backtick_strings: bool },
+    // Python: # comments, and triple-quoted strings that serve as docstrings
+    Python,
+}
+
+/**
+ * Get the language of a source file from its extension, or None if it is not a language we know.
+ */
+pub fn get_language(extension: &str) -> Option<Language> {
+    match extension {
+        "rs" => Some(Language::CStyle { nested_comments: true, backtick_strings: false }),
+        "c" | "h" | "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" | "java" => {
+            Some(Language::CStyle { nested_comments: false, backtick_strings: false })
+        }
+        "go" | "js" | "mjs" | "cjs" | "jsx" | "ts" | "tsx" => Some(Language::CStyle { nested_comments: false, backtick_strings: true }),
+        "py" | "pyi" => Some(Language::Python),
+        _ => None,
+    }
+}
+
+/**
+ * Get the comments (and docstrings) of a source file, one entry per comment; with `include_strings`,
+ * string literals are included too. Consecutive line comments are joined into a single entry.
+ * Identifiers written in camelCase or PascalCase are split into words (snake_case is split later by `clean`).
+ */
+pub fn read_code(file_path: &str, language: Language, include_strings: bool) -> io::Result<Vec<String>> {
+    let source = fs::read_to_string(file_path)?;
+    let pieces = match language {
+        Language::CStyle { nested_comments, backtick_strings } => scan_c_style(&source, nested_comments, backtick_strings, include_strings),
+        Language::Python => scan_python(&source, include_strings),
+    };
+
+    Ok(pieces.iter()
+        .map(|piece| split_identifiers(piece))
+        .filter(|piece| !piece.trim().is_empty())
+        .collect())
+}
+
+/**
+ * Collects comments and strings, joining a line comment onto the previous one when only whitespace
+ * (including a single line break) separates them.
+ */
+struct Pieces {
+    pieces: Vec<String>,
+    // byte offset where the last line comment ended, if it was the last thing collected
+    line_comment_end: Option<usize>,
+}
+
+impl Pieces {
+    fn push_line_comment(&mut self, source: &str, start: usize, end: usize, text: &str) {
+        let continues = self.line_comment_end.is_some_and(|previous| {
+            let between = &source[previous..start];
+            between.trim().is_empty() && between.matches('\n').count() <= 1
+        });
+        match self.pieces.last_mut() {
+            Some(last) if continues => {
+                last.push('\n');
+                last.push_str(text);
+            }
+            _ => self.pieces.push(text.to_string()),
+        }
+        self.line_comment_end = Some(end);
+    }
+
+    fn push(&mut self, text: &str) {
+        self.pieces.push(text.to_string());
+        self.line_comment_end = None;
+    }
+}
+
+fn scan_c_style(source: &str, nested_comments: bool, backtick_strings: bool, include_strings: bool) -> Vec<String> {
+    let bytes = source.as_bytes();
+    let mut pieces = Pieces { pieces: Vec::new(), line_comment_end: None };
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                let end = source[i..].find('\n').map_or(source.len(), |end| i + end);
+                // doc comment markers (///, //!) are not part of the text
+                let text = source[i..end].trim_start_matches('/').trim_start_matches('!');
+                pieces.push_line_comment(source, i, end, text);
+                i = end;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let mut depth = 0;
+                let mut end = i;
+                while end < bytes.len() {
+                    if bytes[end..].starts_with(b"/*") && (depth == 0 || nested_comments) {
+                        depth += 1;
+                        end += 2;
+                    } else if bytes[end..].starts_with(b"*/") {
+                        depth -= 1;
+                        end += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        end += 1;
+                    }
+                }
+                let text = source[i..end.min(source.len())].trim_start_matches("/*").trim_end_matches("*/");
+                pieces.push(text.trim_start_matches(['*', '!']));
+                i = end;
+            }
+            b'"' if is_raw_string_start(bytes, i) => {
+                // Rust raw strings: r"..." or r#"..."#, with any number of #s
+                let hashes = bytes[..i].iter().rev().take_while(|&&b| b == b'#').count();
+                let closing = format!("\"{}", "#".repeat(hashes));
+                let end = source[i + 1..].find(&closing).map_or(source.len(), |end| i + 1 + end);
+                if include_strings {
+                    pieces.push(&source[i + 1..end]);
+                }
+                i = (end + closing.len()).min(source.len());
+            }
+            b'"' => {
+                let end = find_string_end(bytes, i + 1, b'"');
+                if include_strings {
+                    pieces.push(&source[i + 1..end]);
+                }
+                i = end + 1;
+            }
+            b'`' if backtick_strings => {
+                let end = find_string_end(bytes, i + 1, b'`');
+                if include_strings {
+                    pieces.push(&source[i + 1..end]);
+                }
+                i = end + 1;
+            }
+            // a character literal ('x', '\n'); anything else is a Rust lifetime or label and is skipped as code
+            b'\'' => {
+                let end = find_string_end(bytes, i + 1, b'\'');
+                let is_char = end < bytes.len() && source[i + 1..end].chars().count() <= 2 && !source[i + 1..end].contains('\n');
+                i = if is_char { end + 1 } else { i + 1 };
+            }
+            _ => i += 1,
+        }
+    }
+    pieces.pieces
+}
+
+fn is_raw_string_start(bytes: &[u8], quote: usize) -> bool {
+    let hashes = bytes[..quote].iter().rev().take_while(|&&b| b == b'#').count();
+    let prefix_end = quote - hashes;
+    let prefix_start = if bytes[..prefix_end].ends_with(b"br") {
+        prefix_end - 2
+    } else if bytes[..prefix_end].ends_with(b"r") {
+        prefix_end - 1
+    } else {
+        return false;
+    };
+    // the prefix must start a token; otherwise this is an identifier ending in "r" followed by a string
+    prefix_start == 0 || !(bytes[prefix_start - 1].is_ascii_alphanumeric() || bytes[prefix_start - 1] == b'_')
+}
+
+/**
+ * Find the index of the quote that closes a string starting at `start`, skipping backslash escapes.
+ * Returns the length of the source if the string is never closed.
+ */
+fn find_string_end(bytes: &[u8], start: usize, quote: u8) -> usize {
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b if b == quote => return i,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+fn scan_python(source: &str, include_strings: bool) -> Vec<String> {
+    let bytes = source.as_bytes();
+    let mut pieces = Pieces { pieces: Vec::new(), line_comment_end: None };
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'#' => {
+                let end = source[i..].find('\n').map_or(source.len(), |end| i + end);
+                pieces.push_line_comment(source, i, end, source[i..end].trim_start_matches('#'));
+                i = end;
+            }
+            quote @ (b'"' | b'\'') => {
+                let triple = if quote == b'"' { "\"\"\"" } else { "'''" };
+                if source[i..].starts_with(triple) {
+                    // triple-quoted strings are where docstrings live, so they always count
+                    let end = source[i + 3..].find(triple).map_or(source.len(), |end| i + 3 + end);
+                    pieces.push(&source[i + 3..end]);
+                    i = (end + 3).min(source.len());
+                } else {
+                    let end = find_string_end(bytes, i + 1, quote);
+                    if include_strings {
+                        pieces.push(&source[i + 1..end.min(source.len())]);
+                    }
+                    i = end + 1;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    pieces.pieces
+}
+
+/**
+ * Split camelCase and PascalCase identifiers into separate words: "parseHTTPResponse" becomes "parse HTTP Response".
+ */
+pub fn split_identifiers(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut split = String::with_capacity(text.len());
+
+    for (i, &c) in chars.iter().enumerate() {
+        if i > 0 && c.is_uppercase() {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if previous.is_lowercase() || previous.is_ascii_digit() || (previous.is_uppercase() && next_is_lower) {
+                split.push(' ');
+            }
+        }
+        split.push(c);
+    }
+    split
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn c_style_comments_and_strings() {
+        let source = "/// Adds one.\n/// Twice over.\n\nfn f<'a>(x: &'a str) -> char { // trailing\n\
+            let s = \"not // a comment \\\" /* either */\"; let r = r#\"raw \"// text\"#; '\"' }\n\
+            /* outer /* inner */ still outer */ let t = `tick`;";
+        let rust = scan_c_style(source, true, false, true);
+        assert_eq!(rust, [" Adds one.\n Twice over.", " trailing", "not // a comment \\\" /* either */", "raw \"// text", " outer /* inner */ still outer "]);
+
+        // without nesting the first */ ends the comment; backticks delimit strings in Go and JavaScript
+        let js = scan_c_style("/* outer /* inner */ code */ let t = `tick`;", false, true, true);
+        assert_eq!(js, [" outer /* inner ", "tick"]);
+        assert_eq!(scan_c_style("let s = \"text\"; // note", false, false, false), [" note"]);
+    }
+
+    #[test]
+    fn python_docstrings_and_comments() {
+        let source = "def f():\n    '''Return the answer.'''\n    # first line\n    # second line\n\n    # after a blank line\n    return \"it's\"\n";
+        assert_eq!(scan_python(source, false), ["Return the answer.", " first line\n second line", " after a blank line"]);
+        assert_eq!(scan_python(source, true).last().unwrap(), "it's");
+    }
+
+    #[test]
+    fn identifiers_are_split() {
+        assert_eq!(split_identifiers("parseHTTPResponse of MyType2Value and snake_case"), "parse HTTP Response of My Type2 Value and snake_case");
+    }
+}
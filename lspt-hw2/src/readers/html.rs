@@ -0,0 +1,72 @@
+use crate::xml::decode_entities;
+
+// elements whose content is never visible text
+const HIDDEN_ELEMENTS: &[&str] = &["script", "style", "noscript", "template", "svg"];
+
+// elements that start a new line of text
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "br", "dd", "div", "dl", "dt", "figcaption",
+    "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6", "header", "hr", "li", "main", "nav",
+    "ol", "p", "pre", "section", "table", "td", "th", "title", "tr", "ul",
+];
+
+/**
+ * Get the visible text of an HTML page, one line per block (paragraph, heading, list item, table cell...).
+ * Tags, comments, scripts and styles are dropped and character references are decoded.
+ * This is deliberately forgiving: it never fails, however broken the markup is.
+ */
+pub fn extract_text(html: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut hidden: Option<String> = None;
+    let mut rest = html;
+
+    while let Some(open) = rest.find('<') {
+        if hidden.is_none() {
+            current.push_str(&rest[..open]);
+        }
+        rest = &rest[open..];
+
+        if rest.starts_with("<!--") {
+            rest = rest.find("-->").map_or("", |end| &rest[end + 3..]);
+            continue;
+        }
+
+        let close = match rest.find('>') {
+            Some(close) => close,
+            None => break,
+        };
+        let tag = &rest[1..close];
+        rest = &rest[close + 1..];
+
+        let closing = tag.starts_with('/');
+        let name = tag.trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+
+        match &hidden {
+            Some(element) if closing && *element == name => hidden = None,
+            Some(_) => {}
+            None if !closing && HIDDEN_ELEMENTS.contains(&name.as_str()) && !tag.ends_with('/') => hidden = Some(name),
+            None if BLOCK_ELEMENTS.contains(&name.as_str()) => flush_line(&mut current, &mut lines),
+            None => {}
+        }
+    }
+    if hidden.is_none() {
+        current.push_str(rest);
+    }
+    flush_line(&mut current, &mut lines);
+
+    lines
+}
+
+fn flush_line(current: &mut String, lines: &mut Vec<String>) {
+    // line breaks in the source are just whitespace; only block elements end a line
+    let line = decode_entities(current).split_whitespace().collect::<Vec<&str>>().join(" ");
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    current.clear();
+}
@@ -0,0 +1,302 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// environments whose content is mathematics
+const MATH_ENVIRONMENTS: &[&str] = &[
+    "equation", "align", "alignat", "gather", "multline", "flalign", "eqnarray", "displaymath", "math",
+];
+
+// environments whose content is code, drawings or otherwise not prose
+const SKIPPED_ENVIRONMENTS: &[&str] = &[
+    "verbatim", "lstlisting", "minted", "comment", "tikzpicture", "filecontents", "thebibliography",
+];
+
+// environments that take a column specification as their first argument
+const TABULAR_ENVIRONMENTS: &[&str] = &["tabular", "tabularx", "longtable", "array"];
+
+// commands whose arguments are labels, keys, paths or settings rather than text, with how many to drop
+const DROPPED_ARGUMENTS: &[(&str, usize)] = &[
+    ("label", 1), ("ref", 1), ("eqref", 1), ("pageref", 1), ("autoref", 1), ("cref", 1), ("Cref", 1),
+    ("cite", 1), ("citep", 1), ("citet", 1), ("citealp", 1), ("citeauthor", 1), ("nocite", 1),
+    ("usepackage", 1), ("documentclass", 1), ("includegraphics", 1), ("graphicspath", 1),
+    ("bibliography", 1), ("bibliographystyle", 1), ("url", 1), ("href", 1), ("textcolor", 1), ("color", 1),
+    ("pagestyle", 1), ("thispagestyle", 1), ("pagenumbering", 1), ("hypersetup", 1), ("geometry", 1),
+    ("vspace", 1), ("hspace", 1), ("setlength", 2), ("addtolength", 2), ("setcounter", 2),
+    ("newcommand", 2), ("renewcommand", 2), ("providecommand", 2), ("newenvironment", 3), ("renewenvironment", 3),
+];
+
+/**
+ * Get the prose of a LaTeX (.tex) document, following \input and \include.
+ * Comments, mathematics (inline and display), code environments and the preamble are dropped,
+ * as are command names and the arguments of commands that do not hold text (\label, \cite, \usepackage...);
+ * the arguments of text commands such as \emph{} and \section{} are kept.
+ * Included files are looked up relative to the directory of the main file, as LaTeX does.
+ */
+pub fn read_latex(file_path: &str) -> io::Result<Vec<String>> {
+    let base = Path::new(file_path).parent().unwrap_or(Path::new("")).to_path_buf();
+    let mut visited = HashSet::new();
+    let text = read_source(Path::new(file_path), &base, &mut visited)?;
+
+    Ok(text.lines().map(str::trim).filter(|line| !line.is_empty()).map(String::from).collect())
+}
+
+fn read_source(path: &Path, base: &Path, visited: &mut HashSet<PathBuf>) -> io::Result<String> {
+    // a file that includes itself (directly or not) is only read once
+    if !visited.insert(fs::canonicalize(path)?) {
+        return Ok(String::new());
+    }
+
+    let source = strip_comments(&fs::read_to_string(path)?);
+    let body = match source.find("\\begin{document}") {
+        Some(start) => {
+            let body = &source[start + "\\begin{document}".len()..];
+            &body[..body.find("\\end{document}").unwrap_or(body.len())]
+        }
+        None => &source[..],
+    };
+
+    let mut converter = Converter { chars: body.chars().collect(), position: 0, base, visited };
+    converter.convert()
+}
+
+/**
+ * Remove everything from an unescaped % to the end of its line.
+ */
+fn strip_comments(source: &str) -> String {
+    let mut stripped = String::with_capacity(source.len());
+    for line in source.lines() {
+        let mut backslashes = 0;
+        for c in line.chars() {
+            if c == '%' && backslashes % 2 == 0 {
+                break;
+            }
+            backslashes = if c == '\\' { backslashes + 1 } else { 0 };
+            stripped.push(c);
+        }
+        stripped.push('\n');
+    }
+    stripped
+}
+
+/**
+ * Walks a LaTeX body one character at a time, writing out the text it contains.
+ */
+struct Converter<'a> {
+    chars: Vec<char>,
+    position: usize,
+    base: &'a Path,
+    visited: &'a mut HashSet<PathBuf>,
+}
+
+impl Converter<'_> {
+    fn convert(&mut self) -> io::Result<String> {
+        let mut text = String::new();
+
+        while let Some(c) = self.next() {
+            match c {
+                '\\' => self.convert_command(&mut text)?,
+                '$' if self.peek() == Some('$') => {
+                    self.position += 1;
+                    self.skip_past("$$");
+                }
+                '$' => self.skip_past("$"),
+                '{' | '}' => {}
+                '~' | '&' => text.push(' '),
+                _ => text.push(c),
+            }
+        }
+        Ok(text)
+    }
+
+    fn convert_command(&mut self, text: &mut String) -> io::Result<()> {
+        let c = match self.next() {
+            Some(c) => c,
+            None => return Ok(()),
+        };
+
+        if !c.is_ascii_alphabetic() {
+            match c {
+                '\\' => {
+                    self.skip_optional_arguments();
+                    text.push('\n');
+                }
+                '(' => self.skip_past("\\)"),
+                '[' => self.skip_past("\\]"),
+                '%' | '&' | '$' | '#' | '_' | '{' | '}' => text.push(c),
+                ',' | ';' | ':' | '!' | ' ' => text.push(' '),
+                // accents such as \'e or \"{o} leave the letter they decorate
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        let mut name = c.to_string();
+        while let Some(next) = self.peek().filter(char::is_ascii_alphabetic) {
+            name.push(next);
+            self.position += 1;
+        }
+        if self.peek() == Some('*') {
+            self.position += 1;
+        }
+
+        match name.as_str() {
+            "begin" => {
+                let environment = self.read_group().unwrap_or_default();
+                let environment = environment.trim_end_matches('*');
+                if MATH_ENVIRONMENTS.contains(&environment) || SKIPPED_ENVIRONMENTS.contains(&environment) {
+                    let end = format!("\\end{{{}", environment);
+                    self.skip_past(&end);
+                    self.skip_past("}");
+                } else {
+                    self.skip_optional_arguments();
+                    if TABULAR_ENVIRONMENTS.contains(&environment) {
+                        self.read_group();
+                    }
+                }
+                text.push('\n');
+            }
+            "end" => {
+                self.read_group();
+                text.push('\n');
+            }
+            "input" | "include" | "subfile" => {
+                if let Some(file) = self.read_group() {
+                    text.push('\n');
+                    text.push_str(&self.read_included(file.trim())?);
+                    text.push('\n');
+                }
+            }
+            "def" => {
+                // \def\name#1{replacement}
+                while self.peek().is_some_and(|c| c != '{') {
+                    self.position += 1;
+                }
+                self.read_group();
+            }
+            "verb" => {
+                if let Some(delimiter) = self.next() {
+                    self.skip_past(&delimiter.to_string());
+                }
+            }
+            "item" | "par" | "newline" | "linebreak" => {
+                self.skip_optional_arguments();
+                text.push('\n');
+            }
+            _ => {
+                self.skip_optional_arguments();
+                if let Some((_, count)) = DROPPED_ARGUMENTS.iter().find(|(command, _)| *command == name) {
+                    for _ in 0..*count {
+                        self.skip_optional_arguments();
+                        self.read_group();
+                    }
+                }
+                // any other arguments are ordinary groups, so their text is kept
+            }
+        }
+        Ok(())
+    }
+
+    fn read_included(&mut self, file: &str) -> io::Result<String> {
+        let mut path = self.base.join(file);
+        if path.extension().is_none() {
+            path.set_extension("tex");
+        }
+        read_source(&path, self.base, self.visited)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.get(self.position).copied();
+        self.position += 1;
+        c
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    /**
+     * Move past the next occurrence of `delimiter` (or to the end if there is none).
+     */
+    fn skip_past(&mut self, delimiter: &str) {
+        let delimiter: Vec<char> = delimiter.chars().collect();
+        while self.position < self.chars.len() {
+            if self.chars[self.position..].starts_with(&delimiter) {
+                self.position += delimiter.len();
+                return;
+            }
+            // an escaped character can never start the delimiter
+            self.position += if self.chars[self.position] == '\\' { 2 } else { 1 };
+        }
+    }
+
+    /**
+     * Read a {group} (after optional whitespace) and return its raw content, or None if no group follows.
+     */
+    fn read_group(&mut self) -> Option<String> {
+        self.read_balanced('{', '}')
+    }
+
+    fn skip_optional_arguments(&mut self) {
+        while self.read_balanced('[', ']').is_some() {}
+    }
+
+    fn read_balanced(&mut self, open: char, close: char) -> Option<String> {
+        let mut start = self.position;
+        while self.chars.get(start).is_some_and(|c| *c == ' ' || *c == '\t') {
+            start += 1;
+        }
+        if self.chars.get(start) != Some(&open) {
+            return None;
+        }
+
+        let mut depth = 0;
+        let mut i = start;
+        while i < self.chars.len() {
+            match self.chars[i] {
+                '\\' => i += 1,
+                c if c == open => depth += 1,
+                c if c == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.position = i + 1;
+                        return Some(self.chars[start + 1..i].iter().collect());
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        self.position = self.chars.len();
+        Some(self.chars[start + 1..].iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaped_percent_signs_are_not_comments() {
+        assert_eq!(strip_comments("50\\% off % a comment\n\\\\% after a line break\n"), "50\\% off \n\\\\\n");
+    }
+
+    #[test]
+    fn prose_is_kept_and_markup_dropped() {
+        let directory = std::env::temp_dir().join(format!("lspt-hw2-latex-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("main.tex"), "\\documentclass{article}\n\\usepackage{amsmath}\n\\title{Not counted}\n\
+            \\begin{document}\n\\section{Cats}\\label{sec:cats}\nCats are \\emph{small} mammals~\\cite{smith}, with $n = 4$ legs:\n\
+            \\begin{equation}\nx^2 + y^2\n\\end{equation}\n\\begin{verbatim}\nint main;\n\\end{verbatim}\n\
+            \\input{chapter}\n\\end{document}\nAfter the end.\n").unwrap();
+        // a file that includes itself is only read once
+        fs::write(directory.join("chapter.tex"), "They purr \\textbf{loudly}. % not this\n\\input{chapter.tex}\n").unwrap();
+
+        let lines = read_latex(directory.join("main.tex").to_str().unwrap()).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+        let words: Vec<&str> = lines.iter().flat_map(|line| line.split_whitespace()).collect();
+        assert_eq!(words, ["Cats", "Cats", "are", "small", "mammals", ",", "with", "legs:", "They", "purr", "loudly."]);
+    }
+}
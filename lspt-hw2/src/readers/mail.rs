@@ -0,0 +1,435 @@
+use std::fs;
+use std::io;
+
+use crate::readers::{decode_charset, html};
+
+/**
+ * How much of each message body to keep.
+ */
+pub struct MailOptions {
+    // drop quoted reply text ("> ..." lines and their "On ... wrote:" introduction, or a forwarded original message)
+    pub strip_quotes: bool,
+    // drop everything after the "-- " signature separator
+    pub strip_signatures: bool,
+}
+
+/**
+ * The text of one message in an mbox file, with the line number of its "From " separator.
+ */
+pub struct Message {
+    pub line: usize,
+    pub lines: Vec<String>,
+}
+
+/**
+ * Get the text of a single email message (.eml): its subject, followed by the text of its body.
+ * MIME structure is followed: quoted-printable and base64 parts are decoded, attachments are skipped,
+ * and of the alternatives in a multipart/alternative part only the plain text one is used if there is one.
+ */
+pub fn read_eml(file_path: &str, options: &MailOptions) -> io::Result<Vec<String>> {
+    let content = fs::read(file_path)?;
+    Ok(get_message_text(&content, options))
+}
+
+/**
+ * Get the text of every message in an mbox file. Messages are read the same way as by `read_eml`.
+ * Both the mboxo and mboxrd conventions for escaping "From " lines in bodies are understood.
+ */
+pub fn read_mbox(file_path: &str, options: &MailOptions) -> io::Result<Vec<Message>> {
+    let content = fs::read(file_path)?;
+    let mut messages = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+    let mut offset = 0;
+    let mut previous_blank = true;
+
+    for (i, line) in content.split_inclusive(|&b| b == b'\n').enumerate() {
+        // a separator is only recognized at the start of the file or after a blank line
+        if line.starts_with(b"From ") && previous_blank {
+            if let Some((line, begin)) = start {
+                messages.push(Message { line, lines: get_message_text(&unescape_from_lines(&content[begin..offset]), options) });
+            }
+            start = Some((i + 1, offset + line.len()));
+        }
+        previous_blank = trim_line_end(line).is_empty();
+        offset += line.len();
+    }
+
+    match start {
+        Some((line, begin)) => {
+            messages.push(Message { line, lines: get_message_text(&unescape_from_lines(&content[begin..]), options) });
+            Ok(messages)
+        }
+        None if content.is_empty() => Ok(messages),
+        None => Err(io::Error::new(io::ErrorKind::InvalidData, "no \"From \" line; not an mbox file")),
+    }
+}
+
+/**
+ * Undo the escaping of body lines that begin with "From " (">From " becomes "From ", ">>From " becomes ">From ").
+ */
+fn unescape_from_lines(message: &[u8]) -> Vec<u8> {
+    let mut unescaped = Vec::with_capacity(message.len());
+    for line in message.split_inclusive(|&b| b == b'\n') {
+        let quotes = line.iter().take_while(|&&b| b == b'>').count();
+        if quotes > 0 && line[quotes..].starts_with(b"From ") {
+            unescaped.extend_from_slice(&line[1..]);
+        } else {
+            unescaped.extend_from_slice(line);
+        }
+    }
+    unescaped
+}
+
+fn get_message_text(message: &[u8], options: &MailOptions) -> Vec<String> {
+    let (headers, body) = split_headers(message);
+    let mut lines = Vec::new();
+
+    if let Some(subject) = get_header(&headers, "subject") {
+        lines.push(decode_encoded_words(subject));
+    }
+    lines.extend(get_part_text(&headers, body, options));
+    lines
+}
+
+/**
+ * Get the text of one MIME part, recursing into multipart and attached message parts.
+ */
+fn get_part_text(headers: &[(String, String)], body: &[u8], options: &MailOptions) -> Vec<String> {
+    let (content_type, parameters) = parse_header_value(get_header(headers, "content-type").unwrap_or("text/plain"));
+
+    if let Some(disposition) = get_header(headers, "content-disposition") {
+        if parse_header_value(disposition).0 == "attachment" {
+            return Vec::new();
+        }
+    }
+
+    if content_type.starts_with("multipart/") {
+        let boundary = match get_parameter(&parameters, "boundary") {
+            Some(boundary) => boundary,
+            None => return Vec::new(),
+        };
+        let parts: Vec<(String, Vec<String>)> = split_multipart(body, boundary).into_iter()
+            .map(|part| {
+                let (headers, body) = split_headers(part);
+                let (part_type, _) = parse_header_value(get_header(&headers, "content-type").unwrap_or("text/plain"));
+                (part_type, get_part_text(&headers, body, options))
+            })
+            .collect();
+
+        if content_type == "multipart/alternative" {
+            // the same content in several formats: take the plain text if there is any, otherwise the first with text
+            let preferred = parts.iter().position(|(part_type, text)| part_type == "text/plain" && !text.is_empty())
+                .or_else(|| parts.iter().position(|(_, text)| !text.is_empty()));
+            return preferred.map(|i| parts[i].1.clone()).unwrap_or_default();
+        }
+        return parts.into_iter().flat_map(|(_, text)| text).collect();
+    }
+
+    if content_type == "message/rfc822" {
+        return get_message_text(body, options);
+    }
+
+    if content_type != "text/plain" && content_type != "text/html" {
+        return Vec::new();
+    }
+
+    let encoding = get_header(headers, "content-transfer-encoding").unwrap_or("7bit").trim().to_lowercase();
+    let decoded = match encoding.as_str() {
+        "quoted-printable" => decode_quoted_printable(body, false),
+        "base64" => decode_base64(body),
+        _ => body.to_vec(),
+    };
+    let text = decode_charset(&decoded, get_parameter(&parameters, "charset").unwrap_or("us-ascii"));
+
+    if content_type == "text/html" {
+        return html::extract_text(&text);
+    }
+
+    let mut lines: Vec<String> = text.lines().map(String::from).collect();
+    if options.strip_signatures {
+        lines = strip_signature(lines);
+    }
+    if options.strip_quotes {
+        lines = strip_quotes(lines);
+    }
+    lines
+}
+
+/**
+ * Split a message or part into its (unfolded) headers and its body.
+ * Header names are lowercased.
+ */
+fn split_headers(message: &[u8]) -> (Vec<(String, String)>, &[u8]) {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    let mut offset = 0;
+
+    for line in message.split_inclusive(|&b| b == b'\n') {
+        offset += line.len();
+        let line = String::from_utf8_lossy(trim_line_end(line));
+        if line.is_empty() {
+            return (headers, &message[offset..]);
+        }
+
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    (headers, &message[message.len()..])
+}
+
+fn get_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+}
+
+/**
+ * Split a structured header such as `text/plain; charset="utf-8"` into its lowercased value and its parameters.
+ */
+fn parse_header_value(value: &str) -> (String, Vec<(String, String)>) {
+    let mut pieces = Vec::new();
+    let mut piece = String::new();
+    let mut in_quotes = false;
+
+    for c in value.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                piece.push(c);
+            }
+            ';' if !in_quotes => pieces.push(std::mem::take(&mut piece)),
+            _ => piece.push(c),
+        }
+    }
+    pieces.push(piece);
+
+    let main = pieces[0].trim().to_lowercase();
+    let parameters = pieces[1..].iter()
+        .filter_map(|parameter| parameter.split_once('='))
+        .map(|(key, value)| (key.trim().to_lowercase(), value.trim().trim_matches('"').to_string()))
+        .collect();
+    (main, parameters)
+}
+
+fn get_parameter<'a>(parameters: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    parameters.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+}
+
+/**
+ * Get the body parts of a multipart body. The preamble and epilogue around the parts are dropped.
+ */
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{}", boundary);
+    let closing = format!("--{}--", boundary);
+    let mut parts = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut offset = 0;
+
+    for line in body.split_inclusive(|&b| b == b'\n') {
+        let trimmed = String::from_utf8_lossy(trim_line_end(line));
+        let trimmed = trimmed.trim_end();
+        if trimmed == delimiter || trimmed == closing {
+            if let Some(start) = start {
+                parts.push(&body[start..offset]);
+            }
+            if trimmed == closing {
+                return parts;
+            }
+            start = Some(offset + line.len());
+        }
+        offset += line.len();
+    }
+    if let Some(start) = start {
+        parts.push(&body[start..]);
+    }
+    parts
+}
+
+/**
+ * Decode quoted-printable text. In `header` mode (for encoded words) underscores stand for spaces.
+ */
+fn decode_quoted_printable(text: &[u8], header: bool) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < text.len() {
+        match text[i] {
+            b'=' if text[i + 1..].starts_with(b"\r\n") => i += 3,
+            b'=' if text[i + 1..].starts_with(b"\n") => i += 2,
+            b'=' if i + 2 < text.len() && text[i + 1].is_ascii_hexdigit() && text[i + 2].is_ascii_hexdigit() => {
+                let hex = std::str::from_utf8(&text[i + 1..i + 3]).unwrap_or("20");
+                decoded.push(u8::from_str_radix(hex, 16).unwrap_or(b' '));
+                i += 3;
+            }
+            b'_' if header => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            b => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+fn decode_base64(text: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(text.len() * 3 / 4);
+    let mut bits: u32 = 0;
+    let mut bit_count = 0;
+
+    for &b in text {
+        let value = match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a' + 26,
+            b'0'..=b'9' => b - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            _ => continue,
+        };
+        bits = (bits << 6) | value as u32;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            decoded.push((bits >> bit_count) as u8);
+        }
+    }
+    decoded
+}
+
+/**
+ * Decode RFC 2047 encoded words (=?charset?B?...?= and =?charset?Q?...?=) in a header value.
+ * Whitespace between two adjacent encoded words is dropped.
+ */
+fn decode_encoded_words(value: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = value;
+    let mut after_encoded_word = false;
+
+    while let Some(start) = rest.find("=?") {
+        let word = &rest[start + 2..];
+        let parts: Vec<&str> = word.splitn(3, '?').collect();
+        let end = if parts.len() == 3 { parts[2].find("?=") } else { None };
+        let end = match end {
+            Some(end) => end,
+            None => break,
+        };
+
+        let between = &rest[..start];
+        if !(after_encoded_word && between.trim().is_empty()) {
+            decoded.push_str(between);
+        }
+
+        let text = &parts[2].as_bytes()[..end];
+        let bytes = match parts[1] {
+            "B" | "b" => decode_base64(text),
+            _ => decode_quoted_printable(text, true),
+        };
+        decoded.push_str(&decode_charset(&bytes, parts[0]));
+
+        let consumed = parts[0].len() + parts[1].len() + end + 6;
+        rest = &rest[start + consumed..];
+        after_encoded_word = true;
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/**
+ * Remove quoted reply text: lines starting with '>', the "On ... wrote:" line introducing them,
+ * and everything after an "-----Original Message-----" marker.
+ */
+fn strip_quotes(lines: Vec<String>) -> Vec<String> {
+    let end = lines.iter()
+        .position(|line| line.trim().trim_matches('-').trim().eq_ignore_ascii_case("original message"))
+        .unwrap_or(lines.len());
+    let is_quoted = |line: &String| line.trim_start().starts_with('>');
+
+    let mut kept = Vec::new();
+    for (i, line) in lines[..end].iter().enumerate() {
+        if is_quoted(line) {
+            continue;
+        }
+        if line.trim_end().ends_with("wrote:") {
+            let next = lines[i + 1..end].iter().find(|next| !next.trim().is_empty());
+            if next.is_some_and(is_quoted) {
+                continue;
+            }
+        }
+        kept.push(line.to_string());
+    }
+    kept
+}
+
+/**
+ * Remove the signature: everything from the "-- " separator line onwards.
+ */
+fn strip_signature(mut lines: Vec<String>) -> Vec<String> {
+    if let Some(separator) = lines.iter().position(|line| line.trim_end() == "--") {
+        lines.truncate(separator);
+    }
+    lines
+}
+
+fn trim_line_end(line: &[u8]) -> &[u8] {
+    let mut end = line.len();
+    while end > 0 && (line[end - 1] == b'\n' || line[end - 1] == b'\r') {
+        end -= 1;
+    }
+    &line[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn quoted_printable_soft_line_breaks() {
+        let decoded = decode_quoted_printable(b"a long line that was wra=\r\npped, then=\nagain, caf=C3=A9 a_b =3D done=", false);
+        assert_eq!(String::from_utf8(decoded).unwrap(), "a long line that was wrapped, thenagain, café a_b = done=");
+        // in encoded words an underscore is a space
+        assert_eq!(decode_quoted_printable(b"a_b", true), b"a b");
+    }
+
+    #[test]
+    fn base64_ignores_line_breaks_and_padding() {
+        assert_eq!(decode_base64(b"SGVsbG8s\r\nIHdvcmxk\nIQ=="), b"Hello, world!");
+    }
+
+    #[test]
+    fn encoded_word_subjects() {
+        assert_eq!(decode_encoded_words("=?UTF-8?B?SGVsbG8=?= =?ISO-8859-1?Q?caf=E9_au_lait?= today"), "Hellocafé au lait today");
+        assert_eq!(decode_encoded_words("Re: =?utf-8?q?na=C3=AFve?= question"), "Re: naïve question");
+        // an unfinished encoded word is left as it is
+        assert_eq!(decode_encoded_words("odd =?utf-8?q?subject"), "odd =?utf-8?q?subject");
+    }
+
+    #[test]
+    fn quotes_and_signatures_are_stripped() {
+        let body = lines("Sounds good.\n\nOn Monday, Ann wrote:\n> Shall we meet?\n>> Earlier text\nSee you.\n-- \nBob\nBob's Company");
+        let options = MailOptions { strip_quotes: true, strip_signatures: true };
+        let message = format!("Subject: =?utf-8?Q?Re=3A_meeting?=\nContent-Type: text/plain\n\n{}\n", body.join("\n"));
+        assert_eq!(get_message_text(message.as_bytes(), &options), ["Re: meeting", "Sounds good.", "", "See you."]);
+
+        // "wrote:" is only dropped when quoted text follows it
+        assert_eq!(strip_quotes(lines("He wrote:\nthe report")), ["He wrote:", "the report"]);
+        assert_eq!(strip_quotes(lines("Yes.\n-----Original Message-----\nFrom: Ann")), ["Yes."]);
+    }
+
+    #[test]
+    fn alternative_parts_prefer_plain_text() {
+        let message = "Content-Type: multipart/alternative; boundary=\"b1\"\n\npreamble\n--b1\nContent-Type: text/html\n\n<p>html version</p>\n\
+            --b1\nContent-Type: text/plain; charset=utf-8\nContent-Transfer-Encoding: quoted-printable\n\nplain=20version\n--b1--\n";
+        let options = MailOptions { strip_quotes: false, strip_signatures: false };
+        assert_eq!(get_message_text(message.as_bytes(), &options), ["plain version"]);
+    }
+}
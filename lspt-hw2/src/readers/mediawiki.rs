@@ -0,0 +1,370 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+
+use bzip2::read::MultiBzDecoder;
+
+use crate::xml::{decode_entities, XmlEvent, XmlReader};
+
+// link namespaces whose links are not part of the running text
+const HIDDEN_LINK_PREFIXES: &[&str] = &["file", "image", "media", "category"];
+
+// tags whose content is markup or data rather than prose
+const HIDDEN_TAGS: &[&str] = &["ref", "gallery", "math", "chem", "score", "syntaxhighlight", "source", "timeline", "graph", "templatedata"];
+
+/**
+ * One article of a wiki dump: its title and its text with the wiki markup removed, one paragraph per line.
+ */
+pub struct Article {
+    pub title: String,
+    pub lines: Vec<String>,
+}
+
+/**
+ * Whether a file is a MediaWiki XML export, judged from the root element near its start.
+ * A bzip2-compressed file is judged by its decompressed start.
+ */
+pub fn is_dump(file_path: &str) -> io::Result<bool> {
+    let file = File::open(file_path)?;
+    let mut start = Vec::new();
+    if file_path.ends_with(".bz2") {
+        MultiBzDecoder::new(file).take(1024).read_to_end(&mut start)?;
+    } else {
+        file.take(1024).read_to_end(&mut start)?;
+    }
+    Ok(String::from_utf8_lossy(&start).contains("<mediawiki"))
+}
+
+/**
+ * Read a MediaWiki XML dump (optionally bzip2-compressed, as the dumps are published),
+ * calling `add_article` with each article as soon as its page has been read, so whole-language dumps
+ * stream through without being loaded into memory.
+ * Redirects and pages outside the main (article) namespace are skipped. Only the last revision of a page is used.
+ */
+pub fn read_dump(file_path: &str, add_article: &mut dyn FnMut(Article) -> io::Result<()>) -> io::Result<()> {
+    let file = File::open(file_path)?;
+    if file_path.ends_with(".bz2") {
+        read_pages(BufReader::new(MultiBzDecoder::new(file)), add_article)
+    } else {
+        read_pages(BufReader::new(file), add_article)
+    }
+}
+
+fn read_pages<R: BufRead>(reader: R, add_article: &mut dyn FnMut(Article) -> io::Result<()>) -> io::Result<()> {
+    let mut reader = XmlReader::new(reader);
+    let mut current: Option<String> = None;
+    let mut title = String::new();
+    let mut namespace = String::new();
+    let mut text = String::new();
+    let mut redirect = false;
+
+    while let Some(event) = reader.next_event()? {
+        match event {
+            XmlEvent::Start { name, .. } => match name.as_str() {
+                "page" => {
+                    title.clear();
+                    namespace.clear();
+                    redirect = false;
+                }
+                "redirect" => redirect = true,
+                "text" => {
+                    text.clear();
+                    current = Some(name);
+                }
+                "title" | "ns" => current = Some(name),
+                _ => {}
+            },
+            XmlEvent::End { name } => match name.as_str() {
+                "page" => {
+                    let is_redirect = redirect || text.trim_start().to_lowercase().starts_with("#redirect");
+                    if namespace.trim() == "0" && !is_redirect {
+                        add_article(Article { title: title.to_string(), lines: strip_wikitext(&text) })?;
+                    }
+                }
+                "title" | "ns" | "text" => current = None,
+                _ => {}
+            },
+            XmlEvent::Text(content) => match current.as_deref() {
+                Some("title") => title.push_str(&content),
+                Some("ns") => namespace.push_str(&content),
+                Some("text") => text.push_str(&content),
+                _ => {}
+            },
+        }
+    }
+    Ok(())
+}
+
+/**
+ * Turn wikitext into plain text: templates, tables, references, comments and file/category links are removed,
+ * links are replaced by their label, and headings, list markers and bold/italic quotes are stripped.
+ * The result is one line per non-empty line of the source.
+ */
+pub fn strip_wikitext(wikitext: &str) -> Vec<String> {
+    let text = decode_entities(wikitext);
+    let text = remove_between(&text, "<!--", "-->");
+    let text = remove_hidden_tags(&text);
+    let text = remove_templates_and_tables(&text);
+    let text = replace_links(&text);
+    let text = replace_external_links(&text);
+    let text = remove_tags(&text);
+
+    text.lines()
+        .map(strip_line_markup)
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+fn remove_between(text: &str, open: &str, close: &str) -> String {
+    let mut kept = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(open) {
+        kept.push_str(&rest[..start]);
+        rest = match rest[start..].find(close) {
+            Some(end) => &rest[start + end + close.len()..],
+            None => "",
+        };
+    }
+    kept.push_str(rest);
+    kept
+}
+
+/**
+ * Remove tags like <ref>...</ref> together with their content; self-closing ones like <ref name="x"/> too.
+ */
+fn remove_hidden_tags(text: &str) -> String {
+    let mut kept = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('<') {
+        kept.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let tag_end = match rest.find('>') {
+            Some(end) => end,
+            None => break,
+        };
+        let tag = &rest[1..tag_end];
+        let name = tag.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or("").to_lowercase();
+
+        if !HIDDEN_TAGS.contains(&name.as_str()) {
+            kept.push('<');
+            rest = &rest[1..];
+        } else if tag.ends_with('/') {
+            rest = &rest[tag_end + 1..];
+        } else {
+            let close = format!("</{}", name);
+            rest = match find_ignoring_case(rest, &close) {
+                Some(close_start) => {
+                    let after = &rest[close_start..];
+                    after.find('>').map_or("", |end| &after[end + 1..])
+                }
+                None => &rest[tag_end + 1..],
+            };
+        }
+    }
+    kept.push_str(rest);
+    kept
+}
+
+/**
+ * Find `needle` (which must be lowercase ASCII) in `text`, ignoring ASCII case.
+ */
+fn find_ignoring_case(text: &str, needle: &str) -> Option<usize> {
+    text.as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/**
+ * Remove {{templates}} (which may nest) and {| tables |}.
+ */
+fn remove_templates_and_tables(text: &str) -> String {
+    let mut kept = String::with_capacity(text.len());
+    let mut depth = 0;
+    let mut chars = text.chars().peekable();
+    let mut line_start = true;
+
+    while let Some(c) = chars.next() {
+        let next = chars.peek().copied();
+        let opens = c == '{' && (next == Some('{') || (next == Some('|') && line_start));
+        let closes = depth > 0 && next == Some('}') && (c == '}' || (c == '|' && line_start));
+
+        if opens || closes {
+            depth = if opens { depth + 1 } else { depth - 1 };
+            chars.next();
+            line_start = false;
+            continue;
+        }
+        if depth == 0 {
+            kept.push(c);
+        }
+        line_start = c == '\n' || (line_start && (c == ' ' || c == '\t'));
+    }
+    kept
+}
+
+/**
+ * Replace [[internal links]] by their label, or their target when they have none.
+ * File, image and category links are removed along with any links nested in their captions.
+ */
+fn replace_links(text: &str) -> String {
+    let mut replaced = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("[[") {
+        replaced.push_str(&rest[..start]);
+        rest = &rest[start + 2..];
+
+        // find the matching "]]", allowing for links nested in the link text
+        let mut depth = 1;
+        let mut end = None;
+        let mut i = 0;
+        while i + 1 < rest.len() {
+            if rest[i..].starts_with("[[") {
+                depth += 1;
+                i += 2;
+            } else if rest[i..].starts_with("]]") {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(i);
+                    break;
+                }
+                i += 2;
+            } else {
+                i += rest[i..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+        let end = match end {
+            Some(end) => end,
+            None => break,
+        };
+
+        let link = &rest[..end];
+        rest = &rest[end + 2..];
+
+        let target = link.split('|').next().unwrap_or("");
+        let prefix = target.trim_start_matches(':').split(':').next().unwrap_or("").trim().to_lowercase();
+        if target.contains(':') && HIDDEN_LINK_PREFIXES.contains(&prefix.as_str()) {
+            continue;
+        }
+        let label = link.rsplit('|').next().unwrap_or(link);
+        replaced.push_str(&replace_links(label.trim_start_matches(':')));
+    }
+    replaced.push_str(rest);
+    replaced
+}
+
+/**
+ * Replace [http://example.com label] by its label; bare bracketed URLs are removed.
+ */
+fn replace_external_links(text: &str) -> String {
+    let mut replaced = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('[') {
+        replaced.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let is_url = ["[http://", "[https://", "[//", "[ftp://", "[mailto:"].iter().any(|scheme| rest.starts_with(scheme));
+        match rest.find(']') {
+            Some(end) if is_url => {
+                if let Some((_, label)) = rest[1..end].split_once(' ') {
+                    replaced.push_str(label);
+                }
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                replaced.push('[');
+                rest = &rest[1..];
+            }
+        }
+    }
+    replaced.push_str(rest);
+    replaced
+}
+
+/**
+ * Remove any remaining HTML-style tags such as <br/> or <span>, keeping their content.
+ */
+fn remove_tags(text: &str) -> String {
+    let mut kept = String::with_capacity(text.len());
+    let mut in_tag = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '<' if chars.peek().is_some_and(|next| next.is_ascii_alphabetic() || *next == '/') => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                kept.push(' ');
+            }
+            _ if !in_tag => kept.push(c),
+            _ => {}
+        }
+    }
+    kept
+}
+
+/**
+ * Strip the line-level markup: heading '=', list and indent markers, horizontal rules,
+ * bold/italic quotes and behaviour switches like __TOC__.
+ */
+fn strip_line_markup(line: &str) -> String {
+    let mut line = line.trim();
+    if line.starts_with("----") {
+        return String::new();
+    }
+    line = line.trim_matches('=').trim();
+    line = line.trim_start_matches(['*', '#', ':', ';']).trim();
+
+    let line = line.replace("'''", "").replace("''", "");
+    line.split(' ')
+        .filter(|word| !is_behaviour_switch(word))
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .trim()
+        .to_string()
+}
+
+fn is_behaviour_switch(word: &str) -> bool {
+    word.len() > 4 && word.starts_with("__") && word.ends_with("__")
+        && word[2..word.len() - 2].chars().all(|c| c.is_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn nested_templates_and_tables() {
+        let wikitext = "{{Infobox|name={{lang|fr|Chat}}|size={{convert|1|m}}}}The '''cat''' is small.\n\
+            {| class=\"wikitable\"\n|-\n| {{flag|France}} || cell\n|}\nIt purrs.{{citation needed}}";
+        assert_eq!(strip_wikitext(wikitext), ["The cat is small.", "It purrs."]);
+    }
+
+    #[test]
+    fn links_refs_and_comments() {
+        let wikitext = "== History ==\n\
+            [[File:Cat.jpg|thumb|A [[cat]] sleeping]]* A [[Felis catus|house cat]] and a [[dog]]<ref name=\"a\">{{cite|x}}</ref>.\n\
+            <!-- hidden\nnote -->See [https://example.com the site] or [https://example.org].<ref name=\"b\"/> __NOTOC__\n\
+            [[Category:Cats]]";
+        assert_eq!(strip_wikitext(wikitext), ["History", "A house cat and a dog.", "See the site or ."]);
+    }
+
+    #[test]
+    fn compressed_dumps_are_recognized_by_their_content() {
+        let compress = |name: &str, content: &str| {
+            let file_path = std::env::temp_dir().join(format!("lspt-hw2-{}-{}.bz2", name, std::process::id()));
+            let mut encoder = bzip2::write::BzEncoder::new(File::create(&file_path).unwrap(), bzip2::Compression::default());
+            encoder.write_all(content.as_bytes()).unwrap();
+            encoder.finish().unwrap();
+            file_path.to_str().unwrap().to_string()
+        };
+        let dump = compress("dump.xml", "<mediawiki xml:lang=\"en\"><page></page></mediawiki>");
+        let text = compress("text.txt", "just some text");
+        assert!(is_dump(&dump).unwrap());
+        assert!(!is_dump(&text).unwrap());
+        std::fs::remove_file(dump).unwrap();
+        std::fs::remove_file(text).unwrap();
+    }
+}
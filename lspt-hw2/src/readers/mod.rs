@@ -0,0 +1,39 @@
+/**
+ * Readers for the input formats other than plain text.
+ * Each reader pulls the readable text out of its format and hands it back as lines,
+ * which are then split into words by `clean` exactly like the lines of a .txt file.
+ */
+pub mod code;
+pub mod html;
+pub mod latex;
+pub mod mail;
+pub mod mediawiki;
+pub mod office;
+pub mod records;
+pub mod subtitle;
+pub mod tei;
+pub mod warc;
+
+// windows-1252 characters for bytes 0x80-0x9f, which iso-8859-1 leaves as control codes
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2c6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}', '\u{8f}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}', '\u{17e}', '\u{178}',
+];
+
+/**
+ * Turn the bytes of a text body (a mail part, a web page) into a string according to its charset.
+ * Latin-1 style charsets are read as windows-1252 (as mail clients and browsers do); anything else is treated as UTF-8.
+ */
+pub fn decode_charset(bytes: &[u8], charset: &str) -> String {
+    match charset.to_lowercase().as_str() {
+        "iso-8859-1" | "iso-8859-15" | "latin1" | "windows-1252" | "cp1252" => bytes.iter()
+            .map(|&b| match b {
+                0x80..=0x9f => WINDOWS_1252_HIGH[(b - 0x80) as usize],
+                _ => b as char,
+            })
+            .collect(),
+        _ => String::from_utf8_lossy(bytes).to_string(),
+    }
+}
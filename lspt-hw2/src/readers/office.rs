@@ -0,0 +1,145 @@
+use std::fs::File;
+use std::io::{self, Read};
+
+use zip::ZipArchive;
+
+use crate::xml::{XmlEvent, XmlReader};
+
+/**
+ * Which elements of an office document's XML carry text, and which must be left out.
+ */
+struct TextRules {
+    // elements that make up one paragraph (one line of output)
+    paragraphs: &'static [&'static str],
+    // if set, only text directly inside these elements is kept
+    runs: Option<&'static [&'static str]>,
+    // elements whose entire content is ignored
+    skipped: &'static [&'static str],
+    // empty elements that stand for whitespace (tabs, breaks, spaces)
+    spaces: &'static [&'static str],
+}
+
+const DOCX_RULES: TextRules = TextRules {
+    paragraphs: &["w:p"],
+    runs: Some(&["w:t"]),
+    // tracked deletions and the source side of tracked moves
+    skipped: &["w:del", "w:moveFrom"],
+    spaces: &["w:tab", "w:br", "w:cr"],
+};
+
+const ODT_RULES: TextRules = TextRules {
+    paragraphs: &["text:p", "text:h"],
+    runs: None,
+    // the change log holds the text of tracked deletions; note citations are just footnote numbers
+    skipped: &["text:tracked-changes", "text:note-citation"],
+    spaces: &["text:s", "text:tab", "text:line-break"],
+};
+
+/**
+ * Get the paragraphs of a Word (.docx) document, one per line.
+ * Body text (including tables) comes first, followed by footnotes and endnotes.
+ * Text deleted with change tracking turned on is not included.
+ */
+pub fn read_docx(file_path: &str) -> io::Result<Vec<String>> {
+    let mut archive = open_archive(file_path)?;
+    let mut lines = Vec::new();
+
+    for entry in ["word/document.xml", "word/footnotes.xml", "word/endnotes.xml"] {
+        match read_entry(&mut archive, entry)? {
+            Some(content) => lines.extend(extract_paragraphs(&content, &DOCX_RULES)?),
+            None if entry == "word/document.xml" => return Err(missing_entry(file_path, entry)),
+            None => {}
+        }
+    }
+    Ok(lines)
+}
+
+/**
+ * Get the paragraphs and headings of an OpenDocument text (.odt) file, one per line.
+ * Footnotes appear where they are anchored; tables are read cell by cell.
+ * Text deleted with change tracking turned on is not included.
+ */
+pub fn read_odt(file_path: &str) -> io::Result<Vec<String>> {
+    let mut archive = open_archive(file_path)?;
+    match read_entry(&mut archive, "content.xml")? {
+        Some(content) => extract_paragraphs(&content, &ODT_RULES),
+        None => Err(missing_entry(file_path, "content.xml")),
+    }
+}
+
+fn open_archive(file_path: &str) -> io::Result<ZipArchive<File>> {
+    let file = File::open(file_path)?;
+    ZipArchive::new(file).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> io::Result<Option<Vec<u8>>> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+    };
+    let mut content = Vec::new();
+    entry.read_to_end(&mut content)?;
+    Ok(Some(content))
+}
+
+fn missing_entry(file_path: &str, entry: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{} has no {}", file_path, entry))
+}
+
+/**
+ * Walk an office XML part and collect the text of each paragraph according to `rules`.
+ * Empty paragraphs are dropped.
+ */
+fn extract_paragraphs(content: &[u8], rules: &TextRules) -> io::Result<Vec<String>> {
+    let mut reader = XmlReader::new(content);
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+    let mut paragraph_depth = 0;
+    let mut run_depth = 0;
+    let mut skip_depth = 0;
+
+    while let Some(event) = reader.next_event()? {
+        match event {
+            XmlEvent::Start { name, .. } => {
+                if skip_depth > 0 || rules.skipped.contains(&name.as_str()) {
+                    skip_depth += 1;
+                } else if rules.paragraphs.contains(&name.as_str()) {
+                    // a paragraph nested in another (e.g. a footnote body) ends the text before it
+                    flush_paragraph(&mut current, &mut paragraphs);
+                    paragraph_depth += 1;
+                } else if rules.runs.is_some_and(|runs| runs.contains(&name.as_str())) {
+                    run_depth += 1;
+                } else if rules.spaces.contains(&name.as_str()) {
+                    current.push(' ');
+                }
+            }
+            XmlEvent::End { name } => {
+                if skip_depth > 0 {
+                    skip_depth -= 1;
+                } else if rules.paragraphs.contains(&name.as_str()) {
+                    flush_paragraph(&mut current, &mut paragraphs);
+                    paragraph_depth -= 1;
+                } else if rules.runs.is_some_and(|runs| runs.contains(&name.as_str())) {
+                    run_depth -= 1;
+                }
+            }
+            XmlEvent::Text(text) => {
+                let in_run = rules.runs.is_none() || run_depth > 0;
+                if skip_depth == 0 && paragraph_depth > 0 && in_run {
+                    current.push_str(&text);
+                }
+            }
+        }
+    }
+    flush_paragraph(&mut current, &mut paragraphs);
+
+    Ok(paragraphs)
+}
+
+fn flush_paragraph(current: &mut String, paragraphs: &mut Vec<String>) {
+    if !current.trim().is_empty() {
+        paragraphs.push(current.trim().to_string());
+    }
+    current.clear();
+}
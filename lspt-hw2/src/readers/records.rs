@@ -0,0 +1,174 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+use serde_json::Value;
+
+/**
+ * The text of one record of a JSON Lines or CSV file, with the line number the record starts on.
+ */
+pub struct Record {
+    pub line: usize,
+    pub text: String,
+}
+
+/**
+ * Which CSV column holds the text: a 1-based position, or a name found in the header row.
+ * When a column is picked by position, the first row is treated as data like every other row.
+ */
+pub enum Column {
+    Position(usize),
+    Name(String),
+}
+
+impl Column {
+    pub fn parse(spec: &str) -> Column {
+        match spec.parse::<usize>() {
+            Ok(position) if position > 0 => Column::Position(position),
+            _ => Column::Name(spec.to_string()),
+        }
+    }
+}
+
+/**
+ * Get the text of each record of a JSON Lines (.jsonl) file.
+ * `text_field` is a JSON pointer such as "/body" or "/message/text"; a bare name like "body" means a top-level field.
+ * Blank lines and records where the field is missing or not a string are skipped.
+ */
+pub fn read_jsonl(file_path: &str, text_field: &str) -> io::Result<Vec<Record>> {
+    let pointer = if text_field.starts_with('/') { text_field.to_string() } else { format!("/{}", text_field) };
+    let reader = BufReader::new(File::open(file_path)?);
+    let mut records = Vec::new();
+
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let value: Value = serde_json::from_str(&line).map_err(|e| invalid_data(i + 1, e))?;
+        if let Some(Value::String(text)) = value.pointer(&pointer) {
+            records.push(Record { line: i + 1, text: text.to_string() });
+        }
+    }
+    Ok(records)
+}
+
+/**
+ * Get the text of each row of a CSV file (or a TSV file when `delimiter` is a tab).
+ * Quoted fields may contain delimiters, doubled quotes and line breaks.
+ * Rows that are too short to have the column are skipped.
+ */
+pub fn read_csv(file_path: &str, column: &Column, delimiter: char) -> io::Result<Vec<Record>> {
+    let mut reader = BufReader::new(File::open(file_path)?);
+    let mut records = Vec::new();
+    let mut line = 1;
+
+    let index = match column {
+        Column::Position(position) => position - 1,
+        Column::Name(name) => {
+            let header = read_csv_row(&mut reader, delimiter, &mut line)?.unwrap_or_default();
+            match header.iter().position(|field| field.trim() == name) {
+                Some(index) => index,
+                None => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("no column named \"{}\"", name))),
+            }
+        }
+    };
+
+    loop {
+        let start = line;
+        match read_csv_row(&mut reader, delimiter, &mut line)? {
+            Some(mut row) if row.len() > index => records.push(Record { line: start, text: row.swap_remove(index) }),
+            Some(_) => {}
+            None => break,
+        }
+    }
+    Ok(records)
+}
+
+/**
+ * Read one row of fields, following a quoted field onto the next line if it spans several.
+ * `line` is advanced by the number of lines consumed. Returns None at the end of the file.
+ */
+fn read_csv_row<R: BufRead>(reader: &mut R, delimiter: char, line: &mut usize) -> io::Result<Option<Vec<String>>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut text = String::new();
+
+    loop {
+        text.clear();
+        if reader.read_line(&mut text)? == 0 {
+            if in_quotes {
+                return Err(invalid_data(*line, "unterminated quoted field"));
+            }
+            return Ok(None);
+        }
+        *line += 1;
+
+        let mut chars = text.trim_end_matches(['\n', '\r']).chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if in_quotes && chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' if in_quotes => in_quotes = false,
+                '"' if field.is_empty() => in_quotes = true,
+                _ if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut field)),
+                _ => field.push(c),
+            }
+        }
+
+        if in_quotes {
+            field.push('\n');
+        } else {
+            fields.push(field);
+            return Ok(Some(fields));
+        }
+    }
+}
+
+fn invalid_data<E: std::fmt::Display>(line: usize, error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(csv: &str, delimiter: char) -> io::Result<Vec<(usize, Vec<String>)>> {
+        let mut reader = io::Cursor::new(csv);
+        let mut line = 1;
+        let mut rows = Vec::new();
+        loop {
+            let start = line;
+            match read_csv_row(&mut reader, delimiter, &mut line)? {
+                Some(row) => rows.push((start, row)),
+                None => return Ok(rows),
+            }
+        }
+    }
+
+    #[test]
+    fn doubled_quotes_and_delimiters_in_quoted_fields() {
+        let rows = rows("id,text\n1,\"she said \"\"hi, there\"\"\"\n2,plain \"quote\"\n", ',').unwrap();
+        assert_eq!(rows[1].1, ["1", "she said \"hi, there\""]);
+        // a quote inside an unquoted field is kept as it is
+        assert_eq!(rows[2].1, ["2", "plain \"quote\""]);
+    }
+
+    #[test]
+    fn quoted_fields_span_lines() {
+        let rows = rows("1\t\"first line\r\nsecond line\"\tx\n2\tnext\n", '\t').unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0], (1, vec!["1".to_string(), "first line\nsecond line".to_string(), "x".to_string()]));
+        // the second row starts on the third line of the file
+        assert_eq!(rows[1].0, 3);
+    }
+
+    #[test]
+    fn unterminated_quote_is_an_error() {
+        let error = rows("1,\"never closed\n2,more\n", ',').unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
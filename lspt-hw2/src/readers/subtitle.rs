@@ -0,0 +1,105 @@
+use std::fs;
+use std::io;
+
+use crate::xml::decode_entities;
+
+/**
+ * One subtitle cue: the time it appears on screen (in seconds) and its lines of text.
+ */
+pub struct Cue {
+    pub start: f64,
+    pub lines: Vec<String>,
+}
+
+/**
+ * Get the cues of a SubRip (.srt) file.
+ * Cue numbers, timestamps and styling tags (both <i>-style and {\an8}-style) are discarded.
+ */
+pub fn read_srt(file_path: &str) -> io::Result<Vec<Cue>> {
+    let content = fs::read_to_string(file_path)?;
+    Ok(get_blocks(&content).iter().filter_map(|block| parse_cue(block)).collect())
+}
+
+/**
+ * Get the cues of a WebVTT (.vtt) file.
+ * The header, NOTE, STYLE and REGION blocks, cue identifiers, timestamps and cue tags are discarded.
+ */
+pub fn read_vtt(file_path: &str) -> io::Result<Vec<Cue>> {
+    let content = fs::read_to_string(file_path)?;
+    let blocks = get_blocks(&content);
+
+    if !blocks.first().is_some_and(|header| header[0].starts_with("WEBVTT")) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "missing WEBVTT header"));
+    }
+
+    Ok(blocks.iter()
+        .skip(1)
+        .filter(|block| !["NOTE", "STYLE", "REGION"].iter().any(|kind| block[0].starts_with(kind)))
+        .filter_map(|block| parse_cue(block))
+        .collect())
+}
+
+/**
+ * Split a subtitle file into blocks of non-blank lines.
+ */
+fn get_blocks(content: &str) -> Vec<Vec<&str>> {
+    let mut blocks = Vec::new();
+    let mut block = Vec::new();
+
+    for line in content.trim_start_matches('\u{feff}').lines() {
+        if line.trim().is_empty() {
+            if !block.is_empty() {
+                blocks.push(block);
+                block = Vec::new();
+            }
+        } else {
+            block.push(line);
+        }
+    }
+    if !block.is_empty() {
+        blocks.push(block);
+    }
+    blocks
+}
+
+/**
+ * Turn a block into a cue. Anything before the timing line is a cue number or identifier and is dropped.
+ * Blocks without a timing line are not cues and give None.
+ */
+fn parse_cue(block: &[&str]) -> Option<Cue> {
+    let timing = block.iter().position(|line| line.contains("-->"))?;
+    let start = parse_timestamp(block[timing].split("-->").next()?.trim())?;
+    let lines = block[timing + 1..].iter().map(|line| strip_tags(line)).collect();
+
+    Some(Cue { start, lines })
+}
+
+/**
+ * Parse "hh:mm:ss,mmm" (SubRip) or "[hh:]mm:ss.mmm" (WebVTT) into seconds.
+ */
+fn parse_timestamp(timestamp: &str) -> Option<f64> {
+    let mut seconds = 0.0;
+    for part in timestamp.replace(',', ".").split(':') {
+        seconds = seconds * 60.0 + part.trim().parse::<f64>().ok()?;
+    }
+    Some(seconds)
+}
+
+/**
+ * Remove styling markup from a line of cue text and decode its character references.
+ */
+fn strip_tags(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut closing = None;
+
+    for c in line.chars() {
+        match closing {
+            Some(end) if c == end => closing = None,
+            Some(_) => {}
+            None if c == '<' => closing = Some('>'),
+            None if c == '{' => closing = Some('}'),
+            None => stripped.push(c),
+        }
+    }
+    decode_entities(&stripped)
+}
@@ -0,0 +1,199 @@
+use std::fs::File;
+use std::io::{self, BufReader};
+
+use crate::xml::{XmlEvent, XmlReader};
+
+// phrase-level elements that can sit inside a word, so their boundaries must not separate text
+const INLINE_ELEMENTS: &[&str] = &[
+    "abbr", "add", "b", "choice", "corr", "date", "del", "em", "emph", "expan", "foreign", "hi", "i",
+    "mentioned", "name", "num", "orgName", "orig", "persName", "placeName", "q", "quote", "ref", "reg",
+    "rs", "said", "seg", "sic", "soCalled", "span", "strong", "sub", "sup", "supplied", "term", "title",
+    "u", "unclear", "w",
+];
+
+/**
+ * One step of an element path: the element name (or "*"), and whether it may be any descendant
+ * of the previous step ("//") rather than a direct child ("/").
+ */
+struct Step {
+    descendant: bool,
+    name: String,
+}
+
+/**
+ * A simplified XPath location path such as "//body//p" or "/TEI/text/body/div".
+ * Only element names, "*", "/" and "//" are understood; alternatives can be given with "|".
+ * Names without a prefix match elements regardless of their namespace prefix.
+ */
+pub struct ElementPath {
+    alternatives: Vec<Vec<Step>>,
+}
+
+impl ElementPath {
+    pub fn parse(path: &str) -> Result<ElementPath, String> {
+        let mut alternatives = Vec::new();
+
+        for alternative in path.split('|') {
+            let alternative = alternative.trim();
+            if !alternative.starts_with('/') {
+                return Err(format!("element path \"{}\" must start with / or //", alternative));
+            }
+
+            let mut steps = Vec::new();
+            let mut rest = alternative;
+            while !rest.is_empty() {
+                let descendant = rest.starts_with("//");
+                rest = rest.trim_start_matches('/');
+                let end = rest.find('/').unwrap_or(rest.len());
+                let name = &rest[..end];
+                if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || "_-.:*".contains(c)) {
+                    return Err(format!("invalid step \"{}\" in element path \"{}\"", name, alternative));
+                }
+                steps.push(Step { descendant, name: name.to_string() });
+                rest = &rest[end..];
+            }
+            alternatives.push(steps);
+        }
+        Ok(ElementPath { alternatives })
+    }
+
+    /**
+     * Whether the innermost element of `stack` (the names of the open elements, outermost first) is on this path.
+     */
+    fn matches(&self, stack: &[String]) -> bool {
+        self.alternatives.iter().any(|steps| matches_steps(steps, stack))
+    }
+}
+
+fn matches_steps(steps: &[Step], stack: &[String]) -> bool {
+    let (step, earlier) = match steps.split_last() {
+        Some(split) => split,
+        None => return stack.is_empty(),
+    };
+    let (element, ancestors) = match stack.split_last() {
+        Some(split) => split,
+        None => return false,
+    };
+    if !name_matches(&step.name, element) {
+        return false;
+    }
+
+    if step.descendant {
+        // any ancestor (or none, for a leading "//") may match the earlier steps
+        (0..=ancestors.len()).rev().any(|len| matches_steps(earlier, &ancestors[..len]))
+    } else {
+        matches_steps(earlier, ancestors)
+    }
+}
+
+fn name_matches(pattern: &str, name: &str) -> bool {
+    pattern == "*" || pattern == name || (!pattern.contains(':') && local_name(name) == pattern)
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/**
+ * Which parts of an XML file to count.
+ */
+pub struct XmlSelection {
+    // elements whose text is counted, each as its own segment; everything if unset
+    pub select: Option<ElementPath>,
+    // names of elements whose text is never counted, such as TEI's <note>
+    pub exclude: Vec<String>,
+    // elements that each become a document of their own, such as TEI's <div>
+    pub split: Option<ElementPath>,
+}
+
+/**
+ * The selected text of one document in an XML file: one entry per selected element.
+ */
+pub struct XmlDocument {
+    pub name: String,
+    pub segments: Vec<String>,
+}
+
+/**
+ * Get the selected text of an XML file, such as a TEI corpus.
+ * Text of selected elements nested in another selected element counts towards the outer one.
+ * Element boundaries separate words, except for phrase-level elements like <hi> that may fall inside a word.
+ * With `split` set, each matching element (outermost, if they nest) becomes a document named by its
+ * xml:id or n attribute, or by its position; text outside them belongs to a document named after the file.
+ * The markup is parsed as it is read, but the selected text of every document is kept until the end of the file.
+ */
+pub fn read_xml(file_path: &str, selection: &XmlSelection) -> io::Result<Vec<XmlDocument>> {
+    let mut reader = XmlReader::new(BufReader::new(File::open(file_path)?));
+    let mut documents = vec![XmlDocument { name: file_path.to_string(), segments: Vec::new() }];
+    let mut current = 0;
+    let mut stack: Vec<String> = Vec::new();
+    let mut text = String::new();
+    let mut selected_depth = if selection.select.is_none() { Some(0) } else { None };
+    let mut excluded_depth = None;
+    let mut split_depth = None;
+
+    while let Some(event) = reader.next_event()? {
+        match event {
+            XmlEvent::Start { ref name, .. } => {
+                stack.push(name.to_string());
+                if selected_depth.is_some() && !INLINE_ELEMENTS.contains(&local_name(name)) {
+                    text.push(' ');
+                }
+
+                if excluded_depth.is_none() && selection.exclude.iter().any(|excluded| name_matches(excluded, name)) {
+                    excluded_depth = Some(stack.len());
+                }
+
+                if split_depth.is_none() && selection.split.as_ref().is_some_and(|split| split.matches(&stack)) {
+                    flush_segment(&mut text, &mut documents[current]);
+                    let id = event.attribute("xml:id").or(event.attribute("n")).map(String::from)
+                        .unwrap_or_else(|| documents.len().to_string());
+                    documents.push(XmlDocument { name: format!("{}#{}", file_path, id), segments: Vec::new() });
+                    current = documents.len() - 1;
+                    split_depth = Some(stack.len());
+                }
+
+                if selected_depth.is_none() && selection.select.as_ref().is_some_and(|select| select.matches(&stack)) {
+                    selected_depth = Some(stack.len());
+                }
+            }
+            XmlEvent::End { ref name } => {
+                if selected_depth.is_some() && !INLINE_ELEMENTS.contains(&local_name(name)) {
+                    text.push(' ');
+                }
+                if selected_depth == Some(stack.len()) {
+                    flush_segment(&mut text, &mut documents[current]);
+                    selected_depth = None;
+                }
+                if split_depth == Some(stack.len()) {
+                    flush_segment(&mut text, &mut documents[current]);
+                    current = 0;
+                    split_depth = None;
+                }
+                if excluded_depth == Some(stack.len()) {
+                    excluded_depth = None;
+                }
+                stack.pop();
+            }
+            XmlEvent::Text(content) => {
+                if selected_depth.is_some() && excluded_depth.is_none() {
+                    text.push_str(&content);
+                }
+            }
+        }
+    }
+    flush_segment(&mut text, &mut documents[current]);
+
+    // the file itself only counts as a document if it was not split, or has text outside the split elements
+    if documents.len() > 1 && documents[0].segments.is_empty() {
+        documents.remove(0);
+    }
+    Ok(documents)
+}
+
+fn flush_segment(text: &mut String, document: &mut XmlDocument) {
+    if !text.trim().is_empty() {
+        document.segments.push(text.trim().to_string());
+    }
+    text.clear();
+}
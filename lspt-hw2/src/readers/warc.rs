@@ -0,0 +1,167 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+
+use flate2::read::{MultiGzDecoder, ZlibDecoder};
+
+use crate::readers::{decode_charset, html};
+
+/**
+ * One page captured in a WARC file: its target URI and its text.
+ */
+pub struct WarcPage {
+    pub uri: String,
+    pub lines: Vec<String>,
+}
+
+/**
+ * Read a WARC file (optionally gzip-compressed per record, as .warc.gz files are), calling `add_page` with each
+ * successful (2xx) response record as soon as it has been read.
+ * The HTTP Content-Type decides how the body is read: HTML pages go through the HTML extractor, plain text is kept
+ * as it is, and anything else (images, scripts, PDFs...) is skipped.
+ * A record whose body cannot be decoded is reported under its target URI and skipped.
+ */
+pub fn read_warc(file_path: &str, add_page: &mut dyn FnMut(WarcPage) -> io::Result<()>) -> io::Result<()> {
+    let file = File::open(file_path)?;
+    if file_path.ends_with(".gz") {
+        read_records(BufReader::new(MultiGzDecoder::new(file)), file_path, add_page)
+    } else {
+        read_records(BufReader::new(file), file_path, add_page)
+    }
+}
+
+fn read_records<R: BufRead>(mut reader: R, file_path: &str, add_page: &mut dyn FnMut(WarcPage) -> io::Result<()>) -> io::Result<()> {
+    let mut line = String::new();
+
+    loop {
+        // records are separated by blank lines
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with("WARC/") {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("expected a WARC record, found \"{}\"", line.trim())));
+        }
+
+        let headers = read_headers(&mut reader)?;
+        let length: u64 = get_header(&headers, "content-length")
+            .and_then(|length| length.parse().ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "WARC record without a Content-Length"))?;
+        let mut block = Vec::new();
+        (&mut reader).take(length).read_to_end(&mut block)?;
+
+        if get_header(&headers, "warc-type") != Some("response") {
+            continue;
+        }
+        let uri = get_header(&headers, "warc-target-uri").unwrap_or("").trim_matches(['<', '>']).to_string();
+
+        match read_response(&block) {
+            Ok(Some(lines)) => add_page(WarcPage { uri, lines })?,
+            Ok(None) => {}
+            Err(e) => eprintln!("ERROR: cannot read \"{}\" in \"{}\": {}", uri, file_path, e),
+        }
+    }
+}
+
+/**
+ * Read "Name: value" header lines up to the blank line that ends them. Names are lowercased.
+ */
+fn read_headers<R: BufRead>(reader: &mut R) -> io::Result<Vec<(String, String)>> {
+    let mut headers = Vec::new();
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            return Ok(headers);
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+}
+
+fn get_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+}
+
+/**
+ * Get the text of an HTTP response, or None if it is not a successful response with text in it.
+ */
+fn read_response(block: &[u8]) -> io::Result<Option<Vec<String>>> {
+    let mut reader = block;
+    let mut status = String::new();
+    reader.read_line(&mut status)?;
+    let successful = status.split_whitespace().nth(1).is_some_and(|code| code.starts_with('2'));
+    if !status.starts_with("HTTP/") || !successful {
+        return Ok(None);
+    }
+
+    let headers = read_headers(&mut reader)?;
+    let content_type = get_header(&headers, "content-type").unwrap_or("text/html").to_lowercase();
+    let media_type = content_type.split(';').next().unwrap_or("").trim().to_string();
+    let is_html = media_type == "text/html" || media_type == "application/xhtml+xml";
+    if !is_html && media_type != "text/plain" {
+        return Ok(None);
+    }
+
+    let mut body = reader.to_vec();
+    if get_header(&headers, "transfer-encoding").is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked")) {
+        body = decode_chunked(&body)?;
+    }
+    body = match get_header(&headers, "content-encoding").map(|encoding| encoding.to_lowercase()) {
+        None => body,
+        Some(encoding) if encoding == "identity" => body,
+        Some(encoding) if encoding == "gzip" || encoding == "x-gzip" => read_all(MultiGzDecoder::new(&body[..]))?,
+        Some(encoding) if encoding == "deflate" => read_all(ZlibDecoder::new(&body[..]))?,
+        Some(encoding) => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unsupported content encoding \"{}\"", encoding))),
+    };
+
+    let charset = content_type.split(';')
+        .filter_map(|parameter| parameter.trim().strip_prefix("charset="))
+        .next()
+        .unwrap_or("utf-8")
+        .trim_matches('"');
+    let text = decode_charset(&body, charset);
+
+    if is_html {
+        Ok(Some(html::extract_text(&text)))
+    } else {
+        Ok(Some(text.lines().map(String::from).collect()))
+    }
+}
+
+/**
+ * Undo HTTP chunked transfer encoding.
+ */
+fn decode_chunked(mut body: &[u8]) -> io::Result<Vec<u8>> {
+    let mut decoded = Vec::new();
+    let mut size_line = String::new();
+
+    loop {
+        size_line.clear();
+        if body.read_line(&mut size_line)? == 0 {
+            return Ok(decoded);
+        }
+        let size_text = size_line.split(';').next().unwrap_or("").trim();
+        if size_text.is_empty() {
+            continue;
+        }
+        let size = usize::from_str_radix(size_text, 16)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("bad chunk size \"{}\"", size_text)))?;
+        if size == 0 {
+            return Ok(decoded);
+        }
+        let size = size.min(body.len());
+        decoded.extend_from_slice(&body[..size]);
+        body = &body[size..];
+    }
+}
+
+fn read_all<R: Read>(mut reader: R) -> io::Result<Vec<u8>> {
+    let mut content = Vec::new();
+    reader.read_to_end(&mut content)?;
+    Ok(content)
+}
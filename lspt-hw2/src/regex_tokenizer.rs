@@ -0,0 +1,71 @@
+use std::fs;
+
+use regex::Regex;
+
+/**
+ * A tokenizer defined by the user instead of `clean`'s built-in rules, for corpora whose words are not just letters:
+ * chemical formulas, log fields, legal citations...
+ * The text is first cut at every match of the pre-split rules, then each token regex match in what remains is a word.
+ */
+#[derive(Clone)]
+pub struct RegexTokenizer {
+    token: Regex,
+    splits: Vec<Regex>,
+}
+
+impl RegexTokenizer {
+    /**
+     * Load a tokenizer from a config file of "key = value" lines (blank lines and lines starting with '#' are ignored):
+     *   token = REGEX   what a word looks like (required, once)
+     *   split = REGEX   where to cut the text before looking for words, so no word spans a match (any number)
+     * For example, "token = [A-Z][a-z]?\d*(?:[A-Z][a-z]?\d*)*" and "split = \s+".
+     */
+    pub fn load(file_path: &str) -> Result<RegexTokenizer, String> {
+        let config = fs::read_to_string(file_path)
+            .map_err(|e| format!("cannot read tokenizer config \"{}\": {}", file_path, e))?;
+        let error = |number: usize, message: String| format!("tokenizer config \"{}\" line {}: {}", file_path, number + 1, message);
+
+        let mut token = None;
+        let mut splits = Vec::new();
+        for (number, line) in config.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| error(number, format!("expected \"key = value\", found \"{}\"", line)))?;
+            let (key, value) = (key.trim(), value.trim());
+            if key != "token" && key != "split" {
+                return Err(error(number, format!("unknown key \"{}\" (expected token or split)", key)));
+            }
+            let regex = Regex::new(value).map_err(|e| error(number, format!("invalid {} regex: {}", key, e)))?;
+            if regex.is_match("") {
+                return Err(error(number, format!("{} regex \"{}\" matches the empty string", key, value)));
+            }
+
+            match key {
+                "token" if token.is_some() => return Err(error(number, "token regex given more than once".to_string())),
+                "token" => token = Some(regex),
+                _ => splits.push(regex),
+            }
+        }
+
+        let token = token.ok_or_else(|| format!("tokenizer config \"{}\" has no token regex", file_path))?;
+        Ok(RegexTokenizer { token, splits })
+    }
+
+    /**
+     * Get the words of a piece of text, in order.
+     */
+    pub fn tokens(&self, text: &str) -> Vec<String> {
+        let mut pieces = vec![text];
+        for split in &self.splits {
+            pieces = pieces.into_iter().flat_map(|piece| split.split(piece)).collect();
+        }
+
+        pieces.into_iter()
+            .flat_map(|piece| self.token.find_iter(piece).map(|found| found.as_str().to_string()))
+            .collect()
+    }
+}
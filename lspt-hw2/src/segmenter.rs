@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::fs;
+
+/**
+ * Whether a character belongs to a script written without spaces between words (Chinese, Japanese, Thai),
+ * so that runs of it have to be segmented into words rather than split on whitespace.
+ */
+pub fn is_unspaced(c: char) -> bool {
+    is_ideograph(c) || matches!(c,
+        '\u{3041}'..='\u{309f}'     // hiragana
+        | '\u{30a0}'..='\u{30ff}'   // katakana, including the long vowel mark
+        | '\u{31f0}'..='\u{31ff}'   // katakana phonetic extensions
+        | '\u{ff66}'..='\u{ff9f}'   // halfwidth katakana
+        | '\u{0e01}'..='\u{0e3a}'   // thai letters and vowel signs
+        | '\u{0e40}'..='\u{0e4e}')  // thai leading vowels, tone marks
+}
+
+/**
+ * Whether a character is a CJK ideograph (hanzi, kanji), each of which usually carries a meaning of its own.
+ */
+pub fn is_ideograph(c: char) -> bool {
+    matches!(c, '\u{4e00}'..='\u{9fff}' | '\u{3400}'..='\u{4dbf}' | '\u{f900}'..='\u{faff}' | '\u{20000}'..='\u{2a6df}')
+}
+
+/**
+ * Split text into runs of unspaced-script characters and runs of everything else, in order,
+ * each with whether it is unspaced.
+ */
+pub fn split_runs(text: &str) -> Vec<(&str, bool)> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    let mut unspaced = false;
+
+    for (i, c) in text.char_indices() {
+        if is_unspaced(c) != unspaced {
+            if i > start {
+                runs.push((&text[start..i], unspaced));
+            }
+            start = i;
+            unspaced = !unspaced;
+        }
+    }
+    if start < text.len() {
+        runs.push((&text[start..], unspaced));
+    }
+    runs
+}
+
+/**
+ * Splits runs of Chinese, Japanese or Thai text into words.
+ * With a lexicon, the segmentation is the most probable sequence of lexicon words (a Viterbi search over the lattice of
+ * every lexicon word found in the run); characters no lexicon word covers are kept together as one unknown word.
+ * Without one, each pair of adjacent characters is taken as a word (overlapping character bigrams), which is crude
+ * but still gives useful statistics.
+ */
+#[derive(Clone)]
+pub struct Segmenter {
+    lexicon: Option<Lexicon>,
+}
+
+#[derive(Clone)]
+struct Lexicon {
+    // the cost (negative log probability) of each word
+    costs: HashMap<String, f64>,
+    // the length in characters of the longest word
+    longest: usize,
+    // the cost of a character that starts no lexicon word, worse than any word
+    unknown_cost: f64,
+}
+
+impl Segmenter {
+    pub fn bigrams() -> Segmenter {
+        Segmenter { lexicon: None }
+    }
+
+    /**
+     * Load a lexicon with one word per line, optionally followed by whitespace and its frequency
+     * (further fields, such as jieba's part-of-speech tags, are ignored). Words without a frequency count once.
+     */
+    pub fn load(file_path: &str) -> Result<Segmenter, String> {
+        let content = fs::read_to_string(file_path)
+            .map_err(|e| format!("cannot read lexicon \"{}\": {}", file_path, e))?;
+
+        let mut frequencies = HashMap::new();
+        for (number, line) in content.lines().enumerate() {
+            let mut fields = line.split_whitespace();
+            let word = match fields.next() {
+                Some(word) => word,
+                None => continue,
+            };
+            let frequency = match fields.next() {
+                Some(frequency) => frequency.parse::<f64>().ok().filter(|frequency| *frequency > 0.0)
+                    .ok_or_else(|| format!("lexicon \"{}\" line {}: invalid frequency \"{}\"", file_path, number + 1, frequency))?,
+                None => 1.0,
+            };
+            *frequencies.entry(word.to_string()).or_insert(0.0) += frequency;
+        }
+        if frequencies.is_empty() {
+            return Err(format!("lexicon \"{}\" has no words", file_path));
+        }
+
+        let total: f64 = frequencies.values().sum();
+        let longest = frequencies.keys().map(|word| word.chars().count()).max().unwrap_or(1);
+        let costs = frequencies.into_iter().map(|(word, frequency)| (word, (total / frequency).ln())).collect();
+        Ok(Segmenter { lexicon: Some(Lexicon { costs, longest, unknown_cost: total.ln() + 10.0 }) })
+    }
+
+    /**
+     * Get the words of a run of unspaced text, in order.
+     */
+    pub fn segment(&self, run: &str) -> Vec<String> {
+        if let Some(lexicon) = &self.lexicon {
+            return lexicon.segment(&run.chars().collect::<Vec<char>>());
+        }
+
+        let units = bigram_units(run);
+        if units.len() == 1 {
+            return units;
+        }
+        units.windows(2).map(|pair| pair.concat()).collect()
+    }
+}
+
+/**
+ * Split text into the units character bigrams are made of: single characters, except that Thai vowel signs and
+ * tone marks stay with the consonant they are written on.
+ */
+fn bigram_units(run: &str) -> Vec<String> {
+    let mut units: Vec<String> = Vec::new();
+    for c in run.chars() {
+        let combining = matches!(c, '\u{0e31}' | '\u{0e34}'..='\u{0e3a}' | '\u{0e47}'..='\u{0e4e}');
+        match units.last_mut() {
+            Some(unit) if combining => unit.push(c),
+            _ => units.push(c.to_string()),
+        }
+    }
+    units
+}
+
+impl Lexicon {
+    fn segment(&self, chars: &[char]) -> Vec<String> {
+        // best[i]: the lowest cost of segmenting the first i characters, and where its last word starts
+        let mut best: Vec<(f64, usize)> = vec![(f64::INFINITY, 0); chars.len() + 1];
+        best[0] = (0.0, 0);
+
+        for start in 0..chars.len() {
+            let cost_so_far = best[start].0;
+            for length in 1..=self.longest.min(chars.len() - start) {
+                let word: String = chars[start..start + length].iter().collect();
+                let cost = match self.costs.get(&word) {
+                    Some(cost) => *cost,
+                    None if length == 1 => self.unknown_cost,
+                    None => continue,
+                };
+                if cost_so_far + cost < best[start + length].0 {
+                    best[start + length] = (cost_so_far + cost, start);
+                }
+            }
+        }
+
+        // walk back from the end, then join runs of unknown characters into single words
+        let mut bounds = Vec::new();
+        let mut end = chars.len();
+        while end > 0 {
+            bounds.push((best[end].1, end));
+            end = best[end].1;
+        }
+        bounds.reverse();
+
+        let mut words: Vec<String> = Vec::new();
+        let mut previous_unknown = false;
+        for (start, end) in bounds {
+            let word: String = chars[start..end].iter().collect();
+            let unknown = !self.costs.contains_key(&word);
+            match words.last_mut() {
+                Some(last) if unknown && previous_unknown => last.push_str(&word),
+                _ => words.push(word),
+            }
+            previous_unknown = unknown;
+        }
+        words
+    }
+}
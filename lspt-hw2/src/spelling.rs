@@ -0,0 +1,231 @@
+use std::collections::{HashMap, HashSet};
+
+// a word seen at most this often is a candidate misspelling when a much more frequent word is close to it
+const RARE: i32 = 2;
+
+// a correction must be this many times as frequent as the word it corrects
+const RATIO: i32 = 10;
+
+// words this short have too many neighbours at distance 2 for it to mean anything
+const SHORT_WORD: usize = 4;
+
+// a rare word is only reported as a misspelling when its correction is this many times as frequent as it,
+// it was seen next to at least MIN_CONTEXTS words, and its correction has at least MIN_CONFIDENCE
+const MISSPELLING_RATIO: i32 = 100;
+const MIN_CONTEXTS: usize = 2;
+const MIN_CONFIDENCE: f64 = 0.01;
+
+// a correction seen next to a word fewer times than this is put down to chance when measuring confidence
+const MIN_PAIRS: i32 = 2;
+
+/**
+ * A word a misspelling may have been meant as.
+ */
+pub struct Correction {
+    pub word: String,
+    pub distance: usize,
+    pub count: i32,
+    // how often the correction is seen next to the words around the misspelling
+    pub fit: i32,
+    // the smallest share, over the words around the misspelling, of that word's pairs on the same side that are with
+    // the correction ("in the" among the pairs starting with "in"), or 0 when there are no words around it
+    pub confidence: f64,
+}
+
+/**
+ * Suggests corrections of misspelled words from the vocabulary of the corpus, using word frequencies and the bigrams
+ * the words occur in. Candidates are found as in SymSpell: every word of the vocabulary is indexed under the strings
+ * left by deleting up to `max_distance` of its characters, so the words close to a misspelling are the ones sharing
+ * a deletion with it, and only those have their edit distance computed.
+ */
+pub struct Speller {
+    words: HashMap<String, i32>,
+    // the count of each pair of adjacent words, as "first second"
+    bigrams: HashMap<String, i32>,
+    deletes: HashMap<String, Vec<String>>,
+    max_distance: usize,
+}
+
+impl Speller {
+    pub fn new(words: HashMap<String, i32>, bigrams: HashMap<String, i32>, max_distance: usize) -> Speller {
+        let mut deletes: HashMap<String, Vec<String>> = HashMap::new();
+        for word in words.keys() {
+            for delete in get_deletes(word, max_distance) {
+                deletes.entry(delete).or_default().push(word.clone());
+            }
+        }
+        Speller { words, bigrams, deletes, max_distance }
+    }
+
+    /**
+     * Get the words a word may be a misspelling of: words within the edit distance allowed for its length
+     * (at most 1 for short words) that are far more frequent than it, closest first, then the ones that best fit
+     * after the words seen before it and before the words seen after it, then the most frequent.
+     */
+    pub fn corrections(&self, word: &str, before: &[&str], after: &[&str]) -> Vec<Correction> {
+        let count = self.words.get(word).copied().unwrap_or(0);
+        if count > RARE {
+            return Vec::new();
+        }
+        let max_distance = if word.chars().count() <= SHORT_WORD { self.max_distance.min(1) } else { self.max_distance };
+
+        let mut seen = HashSet::new();
+        let mut corrections = Vec::new();
+        for delete in get_deletes(word, max_distance) {
+            for candidate in self.deletes.get(&delete).into_iter().flatten() {
+                if candidate == word || !seen.insert(candidate) {
+                    continue;
+                }
+                let candidate_count = self.words[candidate];
+                let distance = get_edit_distance(word, candidate);
+                if distance <= max_distance && candidate_count >= count.max(1) * RATIO {
+                    let bigram = |first: &str, second: &str| self.bigrams.get(&format!("{} {}", first, second)).copied().unwrap_or(0);
+                    let fits: Vec<(i32, &str)> = before.iter().map(|before| (bigram(before, candidate), *before))
+                        .chain(after.iter().map(|after| (bigram(candidate, after), *after)))
+                        .collect();
+                    let fit = fits.iter().map(|(fit, _)| fit).sum();
+                    let confidence = fits.iter()
+                        .map(|&(fit, neighbour)| match fit {
+                            fit if fit < MIN_PAIRS => 0.0,
+                            fit => f64::from(fit) / f64::from(self.words.get(neighbour).copied().unwrap_or(0).max(1)),
+                        })
+                        .reduce(f64::min)
+                        .unwrap_or(0.0);
+                    corrections.push(Correction { word: candidate.clone(), distance, count: candidate_count, fit, confidence });
+                }
+            }
+        }
+
+        corrections.sort_by(|a, b| a.distance.cmp(&b.distance)
+            .then_with(|| b.fit.cmp(&a.fit))
+            .then_with(|| b.count.cmp(&a.count))
+            .then_with(|| a.word.cmp(&b.word)));
+        corrections
+    }
+
+    /**
+     * Get the rare words of the corpus that look like misspellings of a frequent one, with their count and best correction.
+     * With no dictionary to tell a misspelling from a rare but real word, the correction must be far more frequent than
+     * the word, and be seen often next to every word the rare word was seen next to, on the same side: "in teh house" is
+     * taken for "in the house" since "in the" and "the house" are common, while "the sand was" is not taken for
+     * "the and was". Real words still get through when they fit where a frequent word does, and misspellings get missed
+     * when their correction is not common enough next to their neighbours.
+     */
+    pub fn misspellings(&self) -> Vec<(String, i32, Correction)> {
+        // the words seen before and after each rare word
+        let mut neighbours: HashMap<&str, (Vec<&str>, Vec<&str>)> = HashMap::new();
+        for pair in self.bigrams.keys() {
+            let (first, second) = match pair.split_once(' ') {
+                Some(words) => words,
+                None => continue,
+            };
+            if self.words.get(first).is_some_and(|&count| count <= RARE) {
+                neighbours.entry(first).or_default().1.push(second);
+            }
+            if self.words.get(second).is_some_and(|&count| count <= RARE) {
+                neighbours.entry(second).or_default().0.push(first);
+            }
+        }
+
+        let mut misspellings: Vec<(String, i32, Correction)> = neighbours.iter()
+            .filter_map(|(word, (before, after))| {
+                if before.len() + after.len() < MIN_CONTEXTS {
+                    return None;
+                }
+                let count = self.words[*word];
+                let correction = self.corrections(word, before, after).into_iter()
+                    .find(|correction| correction.count >= count * MISSPELLING_RATIO && correction.confidence >= MIN_CONFIDENCE)?;
+                Some((word.to_string(), count, correction))
+            })
+            .collect();
+        misspellings.sort_by(|a, b| b.2.count.cmp(&a.2.count).then_with(|| a.0.cmp(&b.0)));
+        misspellings
+    }
+}
+
+/**
+ * Get every string left by deleting up to `max_distance` characters of a word, the word itself included.
+ */
+fn get_deletes(word: &str, max_distance: usize) -> HashSet<String> {
+    let mut deletes = HashSet::new();
+    deletes.insert(word.to_string());
+    let mut last: Vec<String> = vec![word.to_string()];
+    for _ in 0..max_distance {
+        let mut next = Vec::new();
+        for shorter in &last {
+            let chars: Vec<char> = shorter.chars().collect();
+            for i in 0..chars.len() {
+                let delete: String = chars[..i].iter().chain(&chars[i + 1..]).collect();
+                if deletes.insert(delete.clone()) {
+                    next.push(delete);
+                }
+            }
+        }
+        last = next;
+    }
+    deletes
+}
+
+/**
+ * Get the number of insertions, deletions, substitutions and swaps of adjacent characters needed to turn one word into
+ * the other (the optimal string alignment distance).
+ */
+fn get_edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // distances[i][j]: the distance between the first i characters of a and the first j of b
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    distances[0] = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1).min(distances[i][j - 1] + 1).min(distances[i - 1][j - 1] + substitution);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_speller(text: &str) -> Speller {
+        let mut words = HashMap::new();
+        let mut bigrams = HashMap::new();
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        for word in &tokens {
+            *words.entry(word.to_string()).or_insert(0) += 1;
+        }
+        for pair in tokens.windows(2) {
+            *bigrams.entry(pair.join(" ")).or_insert(0) += 1;
+        }
+        Speller::new(words, bigrams, 2)
+    }
+
+    #[test]
+    fn misspelling_of_a_frequent_word() {
+        let text = format!("{} they came in teh house", "she went in the house and sat in the room by the window and ".repeat(40));
+        let misspellings = get_speller(&text).misspellings();
+        let found: Vec<(&str, &str)> = misspellings.iter().map(|(word, _, correction)| (word.as_str(), correction.word.as_str())).collect();
+        assert_eq!(found, [("teh", "the")]);
+    }
+
+    #[test]
+    fn rare_word_spelled_like_a_frequent_one() {
+        let text = format!("{} a typo of the and was made", "she went in the house and sat in the room and the window and ".repeat(40))
+            + " they walked on the sand was warm";
+        let speller = get_speller(&text);
+        // "and" is close enough and frequent enough to be a correction, and was even seen once on each side of where "sand" was
+        // seen, but not often enough to tell from chance
+        assert!(speller.corrections("sand", &["the"], &["was"]).iter().any(|correction| correction.word == "and"));
+        assert!(speller.misspellings().is_empty());
+    }
+}
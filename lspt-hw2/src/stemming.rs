@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use rust_stemmers::{Algorithm, Stemmer};
+
+/**
+ * Get the Snowball stemming algorithm for a language name, such as "english" (Porter2) or "german".
+ */
+pub fn parse_algorithm(language: &str) -> Result<Algorithm, String> {
+    match language.to_lowercase().as_str() {
+        "arabic" => Ok(Algorithm::Arabic),
+        "danish" => Ok(Algorithm::Danish),
+        "dutch" => Ok(Algorithm::Dutch),
+        "english" | "porter2" => Ok(Algorithm::English),
+        "finnish" => Ok(Algorithm::Finnish),
+        "french" => Ok(Algorithm::French),
+        "german" => Ok(Algorithm::German),
+        "greek" => Ok(Algorithm::Greek),
+        "hungarian" => Ok(Algorithm::Hungarian),
+        "italian" => Ok(Algorithm::Italian),
+        "norwegian" => Ok(Algorithm::Norwegian),
+        "portuguese" => Ok(Algorithm::Portuguese),
+        "romanian" => Ok(Algorithm::Romanian),
+        "russian" => Ok(Algorithm::Russian),
+        "spanish" => Ok(Algorithm::Spanish),
+        "swedish" => Ok(Algorithm::Swedish),
+        "tamil" => Ok(Algorithm::Tamil),
+        "turkish" => Ok(Algorithm::Turkish),
+        _ => Err(format!("no stemmer for language \"{}\"", language)),
+    }
+}
+
+/**
+ * Reduces words to their stems, remembering which surface forms each stem was seen as
+ * so that reports can show a readable word next to every stem.
+ */
+pub struct Stemming {
+    stemmer: Stemmer,
+    // stem -> surface form -> count
+    surface_forms: HashMap<String, HashMap<String, i32>>,
+}
+
+impl Stemming {
+    pub fn new(algorithm: Algorithm) -> Stemming {
+        Stemming { stemmer: Stemmer::create(algorithm), surface_forms: HashMap::new() }
+    }
+
+    /**
+     * Replace a word by its stem, counting the word as a surface form of that stem.
+     */
+    pub fn stem(&mut self, word: &mut String) {
+        let stem = self.stemmer.stem(word).to_string();
+        self.add_surface_form(&stem, word);
+        *word = stem;
+    }
+
+    /**
+     * Count a word that is left unstemmed (such as a stop word) as a surface form of itself,
+     * so that other words stemmed to it do not take over its label.
+     */
+    pub fn keep(&mut self, word: &str) {
+        self.add_surface_form(word, word);
+    }
+
+    fn add_surface_form(&mut self, stem: &str, word: &str) {
+        let count = self.surface_forms.entry(stem.to_string()).or_default().entry(word.to_string()).or_insert(0);
+        *count += 1;
+    }
+
+    /**
+     * Get the most frequent surface form of a stem (ties go to the alphabetically first), or the stem itself
+     * if it was never seen.
+     */
+    pub fn surface_form<'a>(&'a self, stem: &'a str) -> &'a str {
+        self.surface_forms.get(stem)
+            .and_then(|forms| forms.iter().max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0))))
+            .map_or(stem, |(form, _)| form.as_str())
+    }
+
+    /**
+     * Get how a stemmed word or n-gram is shown in reports: the stems, followed by the most frequent
+     * surface forms in parentheses when they differ, e.g. "run (running)".
+     */
+    pub fn label(&self, stems: &str) -> String {
+        let surface: Vec<&str> = stems.split(' ').map(|stem| self.surface_form(stem)).collect();
+        let surface = surface.join(" ");
+        if surface == stems {
+            surface
+        } else {
+            format!("{} ({})", stems, surface)
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::truecase::SENTENCE_START;
+
+// documents are cut into parts of this many words to measure how evenly a word is spread through the corpus
+pub const PART_SIZE: usize = 1000;
+
+/**
+ * A word proposed as a stop word, with how often it occurs and the share of parts of the corpus it occurs in.
+ */
+pub struct Candidate {
+    pub word: String,
+    pub count: i32,
+    pub dispersion: f64,
+}
+
+/**
+ * Finds the stop words of a corpus from the corpus itself: words that are both frequent and spread evenly across it,
+ * as function words are, while content words, however frequent, cluster in the documents or passages about them.
+ * Each document is cut into parts of `PART_SIZE` words (a short document is a single part), and a word scores
+ * its number of occurrences times the share of parts it occurs in.
+ */
+pub struct StopWordFinder {
+    // for each word, its number of occurrences and the number of parts it occurs in
+    words: HashMap<String, (i32, usize)>,
+    parts: usize,
+}
+
+impl StopWordFinder {
+    pub fn new() -> StopWordFinder {
+        StopWordFinder { words: HashMap::new(), parts: 0 }
+    }
+
+    /**
+     * Count the words of a document, in order. Words are compared in lowercase, as stop words are.
+     */
+    pub fn add_document<'a>(&mut self, words: impl Iterator<Item = &'a String>) {
+        let mut part = HashSet::new();
+        let mut part_length = 0;
+        for word in words {
+            let word = word.strip_prefix(SENTENCE_START).unwrap_or(word).to_lowercase();
+            self.words.entry(word.clone()).or_insert((0, 0)).0 += 1;
+            part.insert(word);
+            part_length += 1;
+            if part_length == PART_SIZE {
+                self.end_part(&mut part);
+                part_length = 0;
+            }
+        }
+        if part_length > 0 {
+            self.end_part(&mut part);
+        }
+    }
+
+    fn end_part(&mut self, part: &mut HashSet<String>) {
+        for word in part.drain() {
+            if let Some((_, parts)) = self.words.get_mut(&word) {
+                *parts += 1;
+            }
+        }
+        self.parts += 1;
+    }
+
+    /**
+     * Get the `count` best stop word candidates, best first.
+     */
+    pub fn candidates(&self, count: usize) -> Vec<Candidate> {
+        let mut candidates: Vec<Candidate> = self.words.iter()
+            .map(|(word, &(occurrences, parts))| Candidate {
+                word: word.clone(),
+                count: occurrences,
+                dispersion: parts as f64 / self.parts.max(1) as f64,
+            })
+            .collect();
+        let score = |candidate: &Candidate| candidate.count as f64 * candidate.dispersion;
+        candidates.sort_by(|a, b| score(b).total_cmp(&score(a)).then_with(|| a.word.cmp(&b.word)));
+        candidates.truncate(count);
+        candidates
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+// marks a capitalized word at the start of a sentence, whose case is decided once the whole corpus has been seen
+pub const SENTENCE_START: char = '\u{1}';
+
+/**
+ * Decides the case of sentence-initial words from how each word is written elsewhere in the corpus:
+ * "The" at the start of a sentence becomes "the" because "the" is far more common than "The" mid-sentence,
+ * while "Winston" stays capitalized.
+ * The corpus is read twice: once to `observe` the case of every word that is not at the start of a sentence,
+ * and once to count, with sentence-initial words `resolve`d first.
+ */
+pub struct Truecaser {
+    // how often each form of a word was seen away from the start of a sentence
+    forms: HashMap<String, i32>,
+}
+
+impl Truecaser {
+    pub fn new() -> Truecaser {
+        Truecaser { forms: HashMap::new() }
+    }
+
+    pub fn observe(&mut self, words: &[String]) {
+        for word in words.iter().filter(|word| !word.starts_with(SENTENCE_START)) {
+            let count = self.forms.entry(word.to_string()).or_insert(0);
+            *count += 1;
+        }
+    }
+
+    /**
+     * Replace every sentence-initial word by its lowercase form if that form is the more common one mid-sentence;
+     * otherwise (including when the word was never seen mid-sentence) it keeps its case.
+     */
+    pub fn resolve(&self, words: &mut [String]) {
+        for word in words.iter_mut() {
+            let written = match word.strip_prefix(SENTENCE_START) {
+                Some(written) => written,
+                None => continue,
+            };
+            let lowercase = written.to_lowercase();
+            let count = |form: &str| self.forms.get(form).copied().unwrap_or(0);
+            *word = if count(&lowercase) > count(written) { lowercase } else { written.to_string() };
+        }
+    }
+}
+
+/**
+ * Split a line into sentences (or the parts of sentences it holds), each with whether it ends its sentence.
+ * A sentence ends at '.', '!' or '?' (after any closing quotes or brackets) followed by whitespace or the end of the line.
+ */
+pub fn split_sentences(line: &str) -> Vec<(&str, bool)> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((_, c)) = chars.next() {
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+        while let Some(&(_, next)) = chars.peek() {
+            if !matches!(next, '.' | '!' | '?' | '"' | '\'' | ')' | ']' | '\u{201d}' | '\u{2019}') {
+                break;
+            }
+            chars.next();
+        }
+        let end = chars.peek().map_or(line.len(), |&(i, _)| i);
+        if chars.peek().is_none_or(|(_, next)| next.is_whitespace()) {
+            sentences.push((&line[start..end], true));
+            start = end;
+        }
+    }
+    if start < line.len() {
+        sentences.push((&line[start..], false));
+    }
+    sentences
+}
@@ -0,0 +1,249 @@
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+
+/**
+ * A single piece of an XML document, as produced by `XmlReader`.
+ * Element names are kept exactly as written, including any namespace prefix (e.g. "w:p").
+ */
+#[derive(Debug)]
+pub enum XmlEvent {
+    Start { name: String, attributes: Vec<(String, String)> },
+    End { name: String },
+    Text(String),
+}
+
+impl XmlEvent {
+    /**
+     * Look up an attribute of a start tag by its (prefixed) name.
+     */
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        match self {
+            XmlEvent::Start { attributes, .. } => attributes.iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.as_str()),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * A small pull parser for the XML found in office documents, TEI corpora and wiki dumps.
+ * It reads from any `BufRead`, so large files are streamed instead of being loaded into memory.
+ *
+ * Comments, processing instructions and doctype declarations are skipped, CDATA sections are returned as text,
+ * and character/entity references in text and attribute values are decoded.
+ * Self-closing tags are reported as a Start immediately followed by the matching End.
+ * No validation is done; the parser only needs to be good enough to find text.
+ */
+pub struct XmlReader<R: BufRead> {
+    reader: R,
+    pending: VecDeque<XmlEvent>,
+}
+
+impl<R: BufRead> XmlReader<R> {
+    pub fn new(reader: R) -> XmlReader<R> {
+        XmlReader { reader, pending: VecDeque::new() }
+    }
+
+    /**
+     * Return the next event, or None at the end of the input.
+     */
+    pub fn next_event(&mut self) -> io::Result<Option<XmlEvent>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(event));
+            }
+
+            let mut buffer = Vec::new();
+            if self.reader.read_until(b'<', &mut buffer)? == 0 {
+                return Ok(None);
+            }
+
+            let at_tag = buffer.last() == Some(&b'<');
+            if at_tag {
+                buffer.pop();
+            }
+            if !buffer.is_empty() {
+                self.pending.push_back(XmlEvent::Text(decode_entities(&to_utf8(buffer)?)));
+            }
+            if at_tag {
+                self.read_tag()?;
+            }
+        }
+    }
+
+    /**
+     * Read everything up to and including the closing '>' of a tag whose '<' was just consumed,
+     * queueing whatever events it produces.
+     */
+    fn read_tag(&mut self) -> io::Result<()> {
+        let mut tag = Vec::new();
+        self.read_to_gt(&mut tag)?;
+
+        if tag.starts_with(b"!--") {
+            while !tag.ends_with(b"-->") {
+                if !self.read_to_gt(&mut tag)? {
+                    break;
+                }
+            }
+        } else if tag.starts_with(b"![CDATA[") {
+            while !tag.ends_with(b"]]>") {
+                if !self.read_to_gt(&mut tag)? {
+                    break;
+                }
+            }
+            let end = tag.len().saturating_sub(3).max(8);
+            self.pending.push_back(XmlEvent::Text(to_utf8(tag[8..end].to_vec())?));
+        } else if tag.starts_with(b"!") {
+            // a doctype may carry an internal subset in square brackets, which can contain '>'
+            while tag.contains(&b'[') && !tag.ends_with(b"]>") && !tag.ends_with(b"] >") {
+                if !self.read_to_gt(&mut tag)? {
+                    break;
+                }
+            }
+        } else if tag.starts_with(b"?") {
+            while !tag.ends_with(b"?>") {
+                if !self.read_to_gt(&mut tag)? {
+                    break;
+                }
+            }
+        } else if let Some(name) = tag.strip_prefix(b"/") {
+            let name = to_utf8(name.to_vec())?;
+            let name = name.trim_end_matches('>').trim().to_string();
+            self.pending.push_back(XmlEvent::End { name });
+        } else {
+            // a '>' inside a quoted attribute value does not end the tag
+            while ends_inside_quotes(&tag) {
+                if !self.read_to_gt(&mut tag)? {
+                    break;
+                }
+            }
+            let tag = to_utf8(tag)?;
+            let tag = tag.trim_end_matches('>');
+            let empty = tag.ends_with('/');
+            let (name, attributes) = parse_start_tag(tag.trim_end_matches('/'));
+            if empty {
+                self.pending.push_back(XmlEvent::Start { name: name.clone(), attributes });
+                self.pending.push_back(XmlEvent::End { name });
+            } else {
+                self.pending.push_back(XmlEvent::Start { name, attributes });
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * Append bytes to `buffer` up to and including the next '>'.
+     * Returns false if the input ended first.
+     */
+    fn read_to_gt(&mut self, buffer: &mut Vec<u8>) -> io::Result<bool> {
+        let read = self.reader.read_until(b'>', buffer)?;
+        Ok(read > 0 && buffer.last() == Some(&b'>'))
+    }
+}
+
+/**
+ * Whether the '>' at the end of `tag` falls inside a quoted attribute value.
+ */
+fn ends_inside_quotes(tag: &[u8]) -> bool {
+    let mut quote = None;
+    for &b in tag {
+        match quote {
+            Some(q) if b == q => quote = None,
+            None if b == b'"' || b == b'\'' => quote = Some(b),
+            _ => {}
+        }
+    }
+    quote.is_some()
+}
+
+/**
+ * Split the inside of a start tag into its name and attributes.
+ */
+fn parse_start_tag(tag: &str) -> (String, Vec<(String, String)>) {
+    let tag = tag.trim();
+    let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+    let name = tag[..name_end].to_string();
+    let mut attributes = Vec::new();
+
+    let mut rest = tag[name_end..].trim_start();
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim().to_string();
+        let value_part = rest[eq + 1..].trim_start();
+        let quote = match value_part.chars().next() {
+            Some(q @ ('"' | '\'')) => q,
+            _ => break,
+        };
+        let value_end = match value_part[1..].find(quote) {
+            Some(end) => end + 1,
+            None => break,
+        };
+        attributes.push((key, decode_entities(&value_part[1..value_end])));
+        rest = value_part[value_end + 1..].trim_start();
+    }
+
+    (name, attributes)
+}
+
+/**
+ * Replace the predefined entities (plus a few common HTML ones) and numeric character references
+ * with the characters they stand for.
+ * Unknown entities are left as written.
+ */
+pub fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let semi = match rest.find(';') {
+            Some(semi) if semi <= 10 => semi,
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        let entity = &rest[1..semi];
+        let replacement = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            // not XML, but common in HTML and in subtitle and wiki text
+            "nbsp" => Some(' '),
+            "ndash" => Some('\u{2013}'),
+            "mdash" => Some('\u{2014}'),
+            "lsquo" => Some('\u{2018}'),
+            "rsquo" => Some('\u{2019}'),
+            "ldquo" => Some('\u{201c}'),
+            "rdquo" => Some('\u{201d}'),
+            "hellip" => Some('\u{2026}'),
+            _ if entity.starts_with("#x") || entity.starts_with("#X") =>
+                u32::from_str_radix(&entity[2..], 16).ok().and_then(char::from_u32),
+            _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(char::from_u32),
+            _ => None,
+        };
+        match replacement {
+            Some(c) => {
+                decoded.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn to_utf8(bytes: Vec<u8>) -> io::Result<String> {
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}